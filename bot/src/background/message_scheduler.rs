@@ -0,0 +1,21 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serenity::http::Http;
+use serenity::model::id::GuildId;
+use tokio::time;
+
+use crate::moodle_stuff::accounts::AccountList;
+use crate::moodle_stuff::message_bridge::forward_messages;
+
+/// forward moodle notifications and messages every 3 mins
+pub async fn forward_continuous(guild: GuildId, http: Arc<Http>) {
+    let mut interval = time::interval(Duration::from_secs(3 * 60));
+
+    loop {
+        interval.tick().await;
+
+        let accounts = AccountList::get_from_file(guild);
+        forward_messages(guild, &accounts, &http).await;
+    }
+}
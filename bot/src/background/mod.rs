@@ -1 +1,2 @@
+pub mod message_scheduler;
 pub mod scan_scheduler;
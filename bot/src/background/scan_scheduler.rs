@@ -25,12 +25,23 @@ pub async fn scan_continuous(guild: GuildId, http: Arc<Http>) {
         let http = http.clone();
         let accounts = AccountList::get_from_file(guild);
         let index = index.clone();
+        let locale = accounts.get_locale();
 
         spawn(async move {
             if let Some((course_name, course_id, client, channels)) =
                 accounts.next_valid_course(index)
             {
-                update_course(course_name, course_id, channels, &http, guild, client).await
+                // failures are already logged by update_course
+                let _ = update_course(
+                    course_name,
+                    course_id,
+                    channels,
+                    &http,
+                    guild,
+                    client,
+                    locale,
+                )
+                .await;
             }
         });
     }
@@ -101,7 +101,7 @@ pub async fn save_new_selection(ctx: &Context, component: MessageComponentIntera
     if let ActionRowComponent::SelectMenu(menu) = &component.message.components[row].components[0] {
         let options: Vec<_> = menu.options.iter().map(|option| &option.value).collect();
 
-        accounts.set_course_map_for_channel(&channel_id, &selection, &options);
+        accounts.set_course_map_for_channel(&channel_id, selection, &options);
 
         let response = component
             .create_interaction_response(&ctx.http, |response| {
@@ -0,0 +1,206 @@
+use serenity::builder::{CreateApplicationCommand, CreateComponents, CreateEmbed};
+use serenity::model::application::component::ButtonStyle;
+use serenity::model::application::interaction::application_command::ApplicationCommandInteraction;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::prelude::interaction::InteractionResponseType;
+use serenity::prelude::Context;
+
+use moodle::data::markdown::html_to_markdown;
+use moodle::data::other_content::grades::GradeItem;
+
+use crate::moodle_stuff::accounts::AccountList;
+use crate::moodle_stuff::course_scanning::describe_moodle_error;
+
+/// grade items per embed page
+const PAGE_SIZE: usize = 10;
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("grades")
+        .description("Shows your grades for a moodle course attached to this channel")
+        .dm_permission(false)
+}
+
+pub async fn run(ctx: &Context, command: ApplicationCommandInteraction) {
+    let guild_id = command
+        .guild_id
+        .expect("This command can only be run in guilds");
+    let accounts = AccountList::get_from_file(guild_id);
+    let courses = accounts.get_own_courses_for_channel(&command.channel_id, command.user.id);
+
+    let res = command
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|msg| {
+                    if courses.is_empty() {
+                        return msg
+                            .content(
+                                "No course of an account you logged in with is attached to this channel (/login normal, /course-selection)",
+                            )
+                            .ephemeral(true);
+                    }
+                    msg.content("Select a course")
+                        .ephemeral(true)
+                        .components(|components| {
+                            components.create_action_row(|actions| {
+                                actions.create_select_menu(|menu| {
+                                    menu.custom_id("grades").options(|options| {
+                                        // a select menu holds at most 25 options
+                                        for (name, id) in courses.iter().take(25) {
+                                            options.create_option(|option| {
+                                                option.value(id).label(name)
+                                            });
+                                        }
+                                        options
+                                    })
+                                })
+                            })
+                        })
+                })
+        })
+        .await;
+
+    if let Err(why) = res {
+        println!("{:#?}", why);
+    }
+}
+
+/// a course was selected in the menu
+pub async fn show_selection(ctx: &Context, component: MessageComponentInteraction) {
+    let course_id = component.data.values.first().and_then(|id| id.parse().ok());
+    if let Some(course_id) = course_id {
+        show_page(ctx, &component, course_id, 0).await;
+    }
+}
+
+/// a page button was pressed, custom id: "gradespage <course_id> <page>"
+pub async fn change_page(ctx: &Context, component: MessageComponentInteraction) {
+    let mut args = component.data.custom_id.split_whitespace().skip(1);
+    let course_id = args.next().and_then(|id| id.parse().ok());
+    let page = args.next().and_then(|page| page.parse().ok());
+    if let (Some(course_id), Some(page)) = (course_id, page) {
+        show_page(ctx, &component, course_id, page).await;
+    }
+}
+
+async fn show_page(
+    ctx: &Context,
+    component: &MessageComponentInteraction,
+    course_id: i64,
+    page: usize,
+) {
+    let guild_id = component
+        .guild_id
+        .expect("This command can only be run in guilds");
+
+    // fetching the grades can take a while
+    let res = component
+        .create_interaction_response(&ctx.http, |response| {
+            response.kind(InteractionResponseType::DeferredUpdateMessage)
+        })
+        .await;
+    if let Err(why) = res {
+        println!("{:#?}", why);
+    }
+
+    let accounts = AccountList::get_from_file(guild_id);
+    let (embed, components) = match accounts.get_own_course(course_id, component.user.id) {
+        None => (
+            error_embed("This course doesnt belong to an account you logged in with"),
+            CreateComponents::default(),
+        ),
+        Some((name, client)) => match client.get_grade_items(course_id).await {
+            Ok(items) => grade_page(name, course_id, &items, page),
+            Err(why) => {
+                println!("Failed to fetch grades of {name}: {why}");
+                (
+                    error_embed(describe_moodle_error(&why).unwrap_or("Failed to fetch grades")),
+                    CreateComponents::default(),
+                )
+            }
+        },
+    };
+
+    let res = component
+        .edit_original_interaction_response(&ctx.http, |response| {
+            response
+                .content("")
+                .set_embed(embed)
+                .set_components(components)
+        })
+        .await;
+    if let Err(why) = res {
+        println!("{:#?}", why);
+    }
+}
+
+fn grade_page(
+    course: &str,
+    course_id: i64,
+    items: &[GradeItem],
+    page: usize,
+) -> (CreateEmbed, CreateComponents) {
+    let pages = items.len().div_ceil(PAGE_SIZE).max(1);
+    let page = page.min(pages - 1);
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .title(format!("Grades: {course}"))
+        .color(0xF98012)
+        .footer(|footer| footer.text(format!("Page {}/{pages}", page + 1)));
+    if items.is_empty() {
+        embed.description("No grade items yet");
+    }
+    for item in items.iter().skip(page * PAGE_SIZE).take(PAGE_SIZE) {
+        let name: String = match item.item_type.as_str() {
+            "course" => "Course total".into(),
+            _ => item.name.chars().take(256).collect(),
+        };
+        embed.field(name, describe_item(item), false);
+    }
+
+    let mut components = CreateComponents::default();
+    if pages > 1 {
+        components.create_action_row(|row| {
+            row.create_button(|button| {
+                button
+                    .custom_id(format!("gradespage {course_id} {}", page.saturating_sub(1)))
+                    .label("◀")
+                    .style(ButtonStyle::Secondary)
+                    .disabled(page == 0)
+            })
+            .create_button(|button| {
+                button
+                    .custom_id(format!("gradespage {course_id} {}", page + 1))
+                    .label("▶")
+                    .style(ButtonStyle::Secondary)
+                    .disabled(page + 1 >= pages)
+            })
+        });
+    }
+    (embed, components)
+}
+
+fn describe_item(item: &GradeItem) -> String {
+    let range = format!("{}–{}", item.min, item.max);
+    let mut lines = vec![match (&item.grade_formatted, item.percentage) {
+        (Some(grade), Some(percentage)) => format!("**{grade}** ({range}, {percentage:.1} %)"),
+        (Some(grade), None) => format!("**{grade}** ({range})"),
+        _ => format!("Not graded yet ({range})"),
+    }];
+    if let Some(graded) = item.graded {
+        lines.push(format!("Graded <t:{graded}:R>"));
+    }
+    if let Some(feedback) = &item.feedback {
+        let feedback: String = html_to_markdown(feedback).chars().take(800).collect();
+        lines.push(format!("__Feedback:__ {feedback}"));
+    }
+    lines.join("\n")
+}
+
+fn error_embed(message: &str) -> CreateEmbed {
+    let mut embed = CreateEmbed::default();
+    embed.title("Grades").color(0xFF0000).description(message);
+    embed
+}
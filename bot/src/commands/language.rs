@@ -0,0 +1,62 @@
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::command::CommandOptionType;
+use serenity::model::application::interaction::application_command::ApplicationCommandInteraction;
+use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::Permissions;
+use serenity::prelude::Context;
+
+use moodle::data::labels::Locale;
+
+use crate::moodle_stuff::accounts::AccountList;
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("language")
+        .description("Sets the language of the moodle updates in this server")
+        .dm_permission(false)
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .create_option(|option| {
+            option
+                .name("language")
+                .description("Language of the entries")
+                .kind(CommandOptionType::String)
+                .required(true)
+                .add_string_choice("English", Locale::En.code())
+                .add_string_choice("Deutsch", Locale::De.code())
+        })
+}
+
+pub async fn run(ctx: &Context, command: ApplicationCommandInteraction) {
+    let guild_id = command
+        .guild_id
+        .expect("This command can only be run in guilds");
+
+    let locale = command
+        .data
+        .options
+        .first()
+        .and_then(|option| option.value.as_ref())
+        .and_then(|value| value.as_str())
+        .and_then(Locale::from_code);
+
+    let message = match locale {
+        Some(locale) => {
+            let mut account_list = AccountList::get_from_file(guild_id);
+            account_list.set_locale(locale);
+            // snapshots only contain machine keys, only multilang texts change with the language
+            "Moodle updates will use the new language from now on"
+        }
+        None => "Unknown language",
+    };
+
+    let res = command
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|msg| msg.content(message).ephemeral(true))
+        })
+        .await;
+    if let Err(why) = res {
+        println!("{:#?}", why);
+    }
+}
@@ -1,4 +1,3 @@
-use anyhow::anyhow;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::application::command::CommandOptionType;
 use serenity::model::application::interaction::application_command::ApplicationCommandInteraction;
@@ -6,7 +5,7 @@ use serenity::model::application::interaction::InteractionResponseType;
 use serenity::model::prelude::interaction::application_command::CommandDataOption;
 use serenity::prelude::Context;
 
-use moodle::Moodle;
+use moodle::{Moodle, MoodleError, MOBILE_SERVICE};
 
 use crate::moodle_stuff::accounts::AccountList;
 
@@ -41,6 +40,15 @@ pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicatio
                         .kind(CommandOptionType::String)
                         .required(true)
                 })
+                .create_sub_option(|option| {
+                    option
+                        .name("service")
+                        .description(
+                            "The web service to request a token for, defaults to the moodle app",
+                        )
+                        .kind(CommandOptionType::String)
+                        .required(false)
+                })
         })
         .create_option(|option| {
             option
@@ -86,31 +94,45 @@ pub async fn run(ctx: &Context, command: ApplicationCommandInteraction) {
     let client = match command.data.options[0].name.as_ref() {
         "normal" => {
             let password = get_string(&options[2]);
-            Moodle::new_with_login(url, name, password).await
+            let service = options
+                .iter()
+                .find(|option| option.name == "service")
+                .map(get_string)
+                .unwrap_or(MOBILE_SERVICE);
+            Moodle::login(url, name, password, service)
+                .await
+                .map(|login| login.client)
         }
         "token" => {
             let token = get_string(&options[2]).to_string();
             Ok(Moodle::new_with_token(url, token))
         }
-        _ => Err(anyhow!("Impossible login form")),
+        _ => {
+            send_response(ctx, &command, "Impossible login form").await;
+            return;
+        }
     };
 
     let mut account_list = AccountList::get_from_file(guild_id);
 
-    match client {
-        Ok(client) => match account_list.add_account(client, name).await {
-            Ok(_) => send_response(&ctx, &command, "Successfully logged in!"),
+    let response = match client {
+        Ok(client) => match account_list
+            .add_account(client, name, command.user.id)
+            .await
+        {
+            Ok(_) => "Successfully logged in!".to_string(),
             Err(why) => {
                 println!("{:#?}", why);
-                send_response(&ctx, &command, "Failed to fetch courses")
+                "Failed to fetch courses".to_string()
             }
         },
+        Err(MoodleError::Login { message, .. }) => format!("Failed to login to moodle: {message}"),
         Err(why) => {
             println!("{:#?}", why);
-            send_response(&ctx, &command, "Failed to login to moodle")
+            "Failed to login to moodle".to_string()
         }
-    }
-    .await;
+    };
+    send_response(ctx, &command, &response).await;
 }
 
 fn get_string(field: &CommandDataOption) -> &str {
@@ -120,7 +142,6 @@ fn get_string(field: &CommandDataOption) -> &str {
         .expect("Required field is empty")
         .as_str()
         .expect("Value not a string")
-        .clone()
 }
 
 async fn send_response(ctx: &Context, command: &ApplicationCommandInteraction, message: &str) {
@@ -83,7 +83,7 @@ pub async fn save_deletion(ctx: &Context, component: MessageComponentInteraction
         .expect("This command can only be run in guilds");
     let mut accounts = AccountList::get_from_file(guild_id);
 
-    accounts.remove_account(&component.data.values.first().unwrap());
+    accounts.remove_account(component.data.values.first().unwrap());
 
     let response = component
         .create_interaction_response(&ctx.http, |response| {
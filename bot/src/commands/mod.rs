@@ -1,4 +1,7 @@
 pub mod course_selection;
+pub mod grades;
+pub mod language;
 pub mod login;
 pub mod logout;
+pub mod progress;
 pub mod update;
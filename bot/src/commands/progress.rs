@@ -0,0 +1,190 @@
+use serenity::builder::{CreateApplicationCommand, CreateEmbed};
+use serenity::model::application::interaction::application_command::ApplicationCommandInteraction;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::prelude::interaction::InteractionResponseType;
+use serenity::prelude::Context;
+
+use moodle::{Moodle, MoodleError};
+
+use crate::moodle_stuff::accounts::AccountList;
+use crate::moodle_stuff::course_scanning::describe_moodle_error;
+
+/// discord allows 25 fields per embed
+const MAX_COURSES: usize = 25;
+/// discord caps field values at 1024 chars
+const MAX_FIELD_LEN: usize = 1000;
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("progress")
+        .description(
+            "Shows your activity completion in the moodle courses attached to this channel",
+        )
+        .dm_permission(false)
+}
+
+pub async fn run(ctx: &Context, command: ApplicationCommandInteraction) {
+    let guild_id = command
+        .guild_id
+        .expect("This command can only be run in guilds");
+
+    // every course needs two requests
+    let res = command
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::DeferredChannelMessageWithSource)
+                .interaction_response_data(|x| x.ephemeral(true))
+        })
+        .await;
+    if let Err(why) = res {
+        println!("{:#?}", why);
+    }
+
+    let accounts = AccountList::get_from_file(guild_id);
+    let courses = accounts.get_own_courses_for_channel(&command.channel_id, command.user.id);
+
+    let mut embed = CreateEmbed::default();
+    embed.title("Progress").color(0xF98012);
+    if courses.is_empty() {
+        embed.description(
+            "No course of an account you logged in with is attached to this channel (/login normal, /course-selection)",
+        );
+    }
+    for (name, course_id) in courses.into_iter().take(MAX_COURSES) {
+        let Some((_, client)) = accounts.get_own_course(course_id, command.user.id) else {
+            continue;
+        };
+        let (title, value) = match course_progress(client, course_id).await {
+            Ok(progress) => progress,
+            Err(why) => {
+                println!("Failed to fetch completion of {name}: {why}");
+                (
+                    "–".into(),
+                    describe_moodle_error(&why)
+                        .unwrap_or("The moodle request failed")
+                        .into(),
+                )
+            }
+        };
+        let name: String = name.chars().take(200).collect();
+        embed.field(format!("{name}: {title}"), value, false);
+    }
+
+    let res = command
+        .edit_original_interaction_response(&ctx.http, |response| response.set_embed(embed))
+        .await;
+    if let Err(why) = res {
+        println!("{:#?}", why);
+    }
+}
+
+/// completion percentage and a list of the open activities
+async fn course_progress(client: &Moodle, course_id: i64) -> Result<(String, String), MoodleError> {
+    let statuses = client.get_activities_completion(course_id).await?;
+    if statuses.is_empty() {
+        return Ok(("–".into(), "This course doesnt track completion".into()));
+    }
+    let modules: Vec<_> = client
+        .get_course_contents(course_id)
+        .await?
+        .into_iter()
+        .flat_map(|section| section.modules)
+        .collect();
+
+    let done = statuses
+        .iter()
+        .filter(|status| status.is_complete())
+        .count();
+    let percentage = done as f64 / statuses.len() as f64 * 100.0;
+    let title = format!("{done}/{} ({percentage:.0} %)", statuses.len());
+
+    let open: Vec<String> = statuses
+        .iter()
+        .filter(|status| !status.is_complete())
+        .map(
+            |status| match modules.iter().find(|module| module.id == status.module_id) {
+                Some(module) => match &module.url {
+                    Some(url) => format!("• [{}]({url})", module.name),
+                    None => format!("• {}", module.name),
+                },
+                None => format!("• {} {}", status.module_name, status.module_id),
+            },
+        )
+        .collect();
+    if open.is_empty() {
+        return Ok((title, "Everything done 🎉".into()));
+    }
+
+    let mut value = String::new();
+    for (index, line) in open.iter().enumerate() {
+        if value.len() + line.len() > MAX_FIELD_LEN {
+            value.push_str(&format!("…and {} more", open.len() - index));
+            break;
+        }
+        value.push_str(line);
+        value.push('\n');
+    }
+    Ok((title, value))
+}
+
+/// the "Mark done" button of a module update was pressed, custom id: "completion <course_id> <module_id>"
+pub async fn toggle_completion(ctx: &Context, component: MessageComponentInteraction) {
+    let guild_id = component
+        .guild_id
+        .expect("This command can only be run in guilds");
+    let mut args = component.data.custom_id.split_whitespace().skip(1);
+    let course_id = args.next().and_then(|id| id.parse().ok());
+    let module_id = args.next().and_then(|id| id.parse().ok());
+    let (Some(course_id), Some(module_id)) = (course_id, module_id) else {
+        return;
+    };
+
+    let res = component
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::DeferredChannelMessageWithSource)
+                .interaction_response_data(|x| x.ephemeral(true))
+        })
+        .await;
+    if let Err(why) = res {
+        println!("{:#?}", why);
+    }
+
+    let accounts = AccountList::get_from_file(guild_id);
+    let message = match accounts.get_own_course(course_id, component.user.id) {
+        None => "Only the person who logged in with the account of this course can mark it done"
+            .to_string(),
+        Some((name, client)) => match toggle(client, course_id, module_id).await {
+            Ok(true) => "Marked as done ✅".to_string(),
+            Ok(false) => "Marked as not done".to_string(),
+            Err(why) => {
+                println!("Failed to update completion in {name}: {why}");
+                describe_moodle_error(&why)
+                    .unwrap_or("The moodle request failed")
+                    .to_string()
+            }
+        },
+    };
+
+    let res = component
+        .edit_original_interaction_response(&ctx.http, |response| response.content(message))
+        .await;
+    if let Err(why) = res {
+        println!("{:#?}", why);
+    }
+}
+
+/// flip the completion of a module, returns the new state
+async fn toggle(client: &Moodle, course_id: i64, module_id: i64) -> Result<bool, MoodleError> {
+    let completed = client
+        .get_activities_completion(course_id)
+        .await?
+        .into_iter()
+        .find(|status| status.module_id == module_id)
+        .map(|status| status.is_complete())
+        .unwrap_or_default();
+    client
+        .update_completion_manually(module_id, !completed)
+        .await?;
+    Ok(!completed)
+}
@@ -4,7 +4,9 @@ use serenity::model::application::interaction::InteractionResponseType;
 use serenity::prelude::Context;
 
 use crate::moodle_stuff::accounts::AccountList;
-use crate::moodle_stuff::course_scanning::update_course;
+use crate::moodle_stuff::course_scanning::{
+    describe_moodle_error, describe_update_error, update_courses,
+};
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
     command
@@ -32,13 +34,39 @@ pub async fn run(ctx: &Context, command: ApplicationCommandInteraction) {
 
     let account_list = AccountList::get_from_file(guild_id);
     let info = account_list.get_manuel_update_info(&channel);
+    let locale = account_list.get_locale();
 
-    for (name, id, client, channels) in info {
-        update_course(name, id, channels, &ctx.http, guild_id, client).await;
+    let mut failures = vec![];
+    for (client, courses) in info {
+        match update_courses(&courses, &ctx.http, guild_id, client, locale).await {
+            Ok(report) => {
+                for (name, why) in report.failed {
+                    failures.push(format!("**{name}:** {}", describe_update_error(&why)));
+                }
+                for (name, why) in report.incomplete {
+                    failures.push(format!(
+                        "**{name}:** Could not scan {} ({})",
+                        why.module_type,
+                        describe_moodle_error(&why.error)
+                            .map_or_else(|| why.error.to_string(), String::from)
+                    ));
+                }
+            }
+            Err(why) => {
+                for (name, _, _) in courses {
+                    failures.push(format!("**{name}:** {}", describe_update_error(&why)));
+                }
+            }
+        }
     }
 
+    let message = if failures.is_empty() {
+        "Finished".to_string()
+    } else {
+        format!("Finished with errors:\n{}", failures.join("\n"))
+    };
     let res = command
-        .edit_original_interaction_response(&ctx.http, |response| response.content("Finished"))
+        .edit_original_interaction_response(&ctx.http, |response| response.content(message))
         .await;
     if let Err(why) = res {
         println!("{}", why);
@@ -4,12 +4,14 @@ use std::env;
 
 use serenity::client::{Context, EventHandler};
 use serenity::model::application::interaction::Interaction;
+use serenity::model::channel::Message;
 use serenity::model::gateway::Ready;
 use serenity::model::prelude::command::Command;
 use serenity::prelude::GatewayIntents;
 use serenity::{async_trait, Client};
 use tokio::spawn;
 
+use crate::background::message_scheduler::forward_continuous;
 use crate::background::scan_scheduler::scan_continuous;
 
 mod background;
@@ -26,8 +28,11 @@ impl EventHandler for Handler {
         let global_commands = Command::set_global_application_commands(&ctx.http, |commands| {
             commands
                 .create_application_command(|command| commands::course_selection::register(command))
+                .create_application_command(|command| commands::grades::register(command))
+                .create_application_command(|command| commands::language::register(command))
                 .create_application_command(|command| commands::login::register(command))
                 .create_application_command(|command| commands::logout::register(command))
+                .create_application_command(|command| commands::progress::register(command))
                 .create_application_command(|command| commands::update::register(command))
         })
         .await;
@@ -40,6 +45,15 @@ impl EventHandler for Handler {
             let http = ctx.http.clone();
             let id = guild.id;
             spawn(async move { scan_continuous(id, http).await });
+            let http = ctx.http.clone();
+            spawn(async move { forward_continuous(id, http).await });
+        }
+    }
+
+    async fn message(&self, ctx: Context, msg: Message) {
+        // replies to forwarded moodle messages are sent back to moodle
+        if msg.guild_id.is_none() && !msg.author.bot {
+            moodle_stuff::message_bridge::answer_reply(&ctx, &msg).await;
         }
     }
 
@@ -47,9 +61,12 @@ impl EventHandler for Handler {
         if let Interaction::ApplicationCommand(command) = interaction {
             match command.data.name.as_str() {
                 "course-selection" => commands::course_selection::run(&ctx, command).await,
+                "grades" => commands::grades::run(&ctx, command).await,
+                "language" => commands::language::run(&ctx, command).await,
                 "login" => commands::login::run(&ctx, command).await,
                 "update" => commands::update::run(&ctx, command).await,
                 "logout" => commands::logout::run(&ctx, command).await,
+                "progress" => commands::progress::run(&ctx, command).await,
                 _ => {}
             };
         } else if let Interaction::MessageComponent(component) = interaction {
@@ -65,6 +82,9 @@ impl EventHandler for Handler {
                     commands::course_selection::save_new_selection(&ctx, component).await
                 }
                 "logout" => commands::logout::save_deletion(&ctx, component).await,
+                "grades" => commands::grades::show_selection(&ctx, component).await,
+                "gradespage" => commands::grades::change_page(&ctx, component).await,
+                "completion" => commands::progress::toggle_completion(&ctx, component).await,
                 _ => {}
             }
         }
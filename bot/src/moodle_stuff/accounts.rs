@@ -4,19 +4,30 @@ use std::fs;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use serenity::model::prelude::{ChannelId, GuildId};
+use serenity::model::prelude::{ChannelId, GuildId, UserId};
+use tokio::sync::mpsc::{self, UnboundedSender};
 
+use moodle::data::labels::Locale;
+use moodle::data::lenient::ParseWarning;
+use moodle::retry::RetryPolicy;
 use moodle::Moodle;
 
+/// (CourseName, Course_id, Channels)
+pub type CourseTarget<'a> = (&'a str, i64, &'a HashSet<ChannelId>);
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AccountList {
     guild: GuildId,
     accounts: HashMap<String, Moodle>, // (AccountName, ClientData)
+    #[serde(default)]
+    owners: HashMap<String, UserId>, // (AccountName, discord user that logged in)
     mapping: IndexMap<String, (i64, String, HashSet<ChannelId>)>, // CourseName, (Course_id, AccountName, Channels)
+    #[serde(default)]
+    locale: Option<Locale>, // language of the module updates, None until /language was used
 }
 
 impl AccountList {
@@ -24,21 +35,80 @@ impl AccountList {
         AccountList {
             guild: guild_id,
             accounts: HashMap::new(),
+            owners: HashMap::new(),
             mapping: IndexMap::new(),
+            locale: None,
         }
     }
 
+    pub fn get_locale(&self) -> Locale {
+        self.locale.unwrap_or_default()
+    }
+
+    pub fn set_locale(&mut self, locale: Locale) {
+        self.locale = Some(locale);
+        self.save_to_file();
+    }
+
+    /// courses of a channel grouped by the account they belong to
     pub fn get_manuel_update_info(
         &self,
         channel_id: &ChannelId,
-    ) -> Vec<(&str, i64, &Moodle, &HashSet<ChannelId>)> {
-        let courses = self.get_active_courses_for_channel(channel_id);
-        courses
+    ) -> Vec<(&Moodle, Vec<CourseTarget<'_>>)> {
+        let mut grouped: IndexMap<&str, (&Moodle, Vec<_>)> = IndexMap::new();
+        for course in self.get_active_courses_for_channel(channel_id) {
+            let (_, account, _) = self.mapping.get(course).unwrap();
+            let (name, id, client, channels) = self.get_course_info(course);
+            grouped
+                .entry(account)
+                .or_insert((client, vec![]))
+                .1
+                .push((name, id, channels));
+        }
+        grouped.into_values().collect()
+    }
+
+    /// courses of a channel that belong to an account the user logged in with
+    pub fn get_own_courses_for_channel(
+        &self,
+        channel_id: &ChannelId,
+        user: UserId,
+    ) -> Vec<(&str, i64)> {
+        self.get_active_courses_for_channel(channel_id)
+            .into_iter()
+            .filter(|course| self.get_owner(&self.mapping[*course].1) == Some(user))
+            .map(|course| (course.as_str(), self.mapping[course].0))
+            .collect()
+    }
+
+    /// name and client of a course, if the user logged in with its account
+    pub fn get_own_course(&self, course_id: i64, user: UserId) -> Option<(&str, &Moodle)> {
+        let (name, (_, account, _)) = self.mapping.iter().find(|(_, (id, account, _))| {
+            *id == course_id && self.get_owner(account) == Some(user)
+        })?;
+        Some((name, self.accounts.get(account)?))
+    }
+
+    /// accounts together with the discord user that logged in with them
+    pub fn get_owned_accounts(&self) -> Vec<(&str, &Moodle, UserId)> {
+        self.accounts
             .iter()
-            .map(|course| self.get_course_info(course))
+            .filter_map(|(name, client)| Some((name.as_str(), client, self.get_owner(name)?)))
             .collect()
     }
 
+    /// client of an account, if the user logged in with it
+    pub fn get_own_account(&self, account: &str, user: UserId) -> Option<&Moodle> {
+        if self.get_owner(account) != Some(user) {
+            return None;
+        }
+        self.accounts.get(account)
+    }
+
+    fn get_owner(&self, account: &str) -> Option<UserId> {
+        self.owners.get(account).copied()
+    }
+
     fn get_active_courses_for_channel(&self, channel_id: &ChannelId) -> Vec<&String> {
         self.mapping
             .iter()
@@ -51,7 +121,7 @@ impl AccountList {
         &self,
         index: Arc<Mutex<usize>>,
     ) -> Option<(&str, i64, &Moodle, &HashSet<ChannelId>)> {
-        if self.mapping.len() == 0 {
+        if self.mapping.is_empty() {
             // no courses registered
             return None;
         }
@@ -130,8 +200,8 @@ impl AccountList {
     pub fn set_course_map_for_channel(
         &mut self,
         channel_id: &ChannelId,
-        selection: &Vec<String>,
-        options: &Vec<&String>,
+        selection: &[String],
+        options: &[&String],
     ) {
         for (course_name, (_, _, channels)) in self.mapping.iter_mut() {
             if selection.contains(course_name) {
@@ -143,9 +213,20 @@ impl AccountList {
         self.save_to_file();
     }
 
-    pub async fn add_account(&mut self, account: Moodle, name: &str) -> Result<(), Box<dyn Error>> {
+    pub async fn add_account(
+        &mut self,
+        account: Moodle,
+        name: &str,
+        owner: UserId,
+    ) -> Result<(), Box<dyn Error>> {
+        let account = configure(account);
         account.get_courses(None).await?;
+        client_cache()
+            .lock()
+            .unwrap()
+            .insert((self.guild, name.into()), account.clone());
         self.accounts.insert(name.into(), account);
+        self.owners.insert(name.into(), owner);
         self.save_to_file();
         Ok(())
     }
@@ -155,7 +236,12 @@ impl AccountList {
     }
 
     pub fn remove_account(&mut self, name: &str) {
+        client_cache()
+            .lock()
+            .unwrap()
+            .remove(&(self.guild, name.into()));
         self.accounts.remove(name);
+        self.owners.remove(name);
 
         self.mapping.retain(|_, (_, acc_name, _)| name != acc_name);
         self.save_to_file();
@@ -164,11 +250,11 @@ impl AccountList {
     fn save_to_file(&self) {
         let file_path = format!("data/{}/accounts.json", &self.guild);
         let file_path = Path::new(&file_path);
-        fs::create_dir_all(&file_path.parent().unwrap()).unwrap();
+        fs::create_dir_all(file_path.parent().unwrap()).unwrap();
 
         if let Ok(account_list) = serde_json::to_string(&self) {
             let byte_array = account_list.as_bytes();
-            if let Ok(mut file) = File::create(&file_path) {
+            if let Ok(mut file) = File::create(file_path) {
                 if let Err(why) = file.write(byte_array) {
                     println!("Couldnt save to file {why}");
                 }
@@ -179,11 +265,78 @@ impl AccountList {
     pub fn get_from_file(guild_id: GuildId) -> AccountList {
         let file_path = format!("data/{}/accounts.json", &guild_id);
         let file_path = Path::new(&file_path);
-        fs::create_dir_all(&file_path.parent().unwrap()).unwrap();
+        fs::create_dir_all(file_path.parent().unwrap()).unwrap();
 
-        match fs::read_to_string(&file_path) {
+        let mut account_list = match fs::read_to_string(file_path) {
             Ok(accounts) => serde_json::from_str::<AccountList>(&accounts).unwrap(),
             Err(_) => AccountList::new(guild_id),
-        }
+        };
+        // the retry policy, the warnings and the language arent stored on disk.
+        // Multilang texts are only resolved once the guild chose a language, otherwise
+        // every guild would see its descriptions change after an update of the bot.
+        // Moodle's own text filters stay off for the same reason
+        let language = account_list.locale.map(Locale::code);
+        let mut cache = client_cache().lock().unwrap();
+        account_list.accounts = account_list
+            .accounts
+            .into_iter()
+            .map(|(name, client)| {
+                // reuse the client of the last call, its site info was already requested
+                let client = cache
+                    .entry((guild_id, name.clone()))
+                    .or_insert_with(|| configure(client))
+                    .clone();
+                let client = match language {
+                    Some(language) => client.with_language(language),
+                    None => client,
+                };
+                (name, client)
+            })
+            .collect();
+        account_list
     }
 }
+
+/// clients of every account by guild and account name, kept alive between scans
+fn client_cache() -> &'static Mutex<HashMap<(GuildId, String), Moodle>> {
+    static CLIENTS: OnceLock<Mutex<HashMap<(GuildId, String), Moodle>>> = OnceLock::new();
+    CLIENTS.get_or_init(Default::default)
+}
+
+/// the settings every client of the bot uses
+fn configure(client: Moodle) -> Moodle {
+    client
+        .with_retry_policy(retry_policy())
+        .with_warnings(warning_log())
+}
+
+/// retry flaky moodle requests and log every failed attempt
+fn retry_policy() -> RetryPolicy {
+    RetryPolicy::default().on_attempt(|attempt| {
+        if let Some(why) = attempt.error {
+            println!(
+                "Moodle request {} failed (attempt {}): {}",
+                attempt.target, attempt.number, why
+            );
+        }
+    })
+}
+
+/// log parts of moodle answers that were skipped because they couldnt be parsed
+fn warning_log() -> UnboundedSender<ParseWarning> {
+    static SENDER: OnceLock<UnboundedSender<ParseWarning>> = OnceLock::new();
+    SENDER
+        .get_or_init(|| {
+            let (sender, mut receiver) = mpsc::unbounded_channel::<ParseWarning>();
+            tokio::spawn(async move {
+                while let Some(warning) = receiver.recv().await {
+                    println!(
+                        "Skipped {} #{} of {}: {}",
+                        warning.item, warning.index, warning.function, warning.message
+                    );
+                }
+            });
+            sender
+        })
+        .clone()
+}
@@ -5,29 +5,42 @@ use std::sync::Arc;
 
 use indexmap::IndexMap;
 use serenity::http::Http;
+use serenity::model::application::component::ButtonStyle;
 use serenity::model::id::ChannelId;
-use serenity::model::prelude::{AttachmentType, GuildId};
+use serenity::model::prelude::{AttachmentType, GuildId, Message};
 use tokio::fs;
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
 
 use moodle::data::comparable::{compare, diff_module_entries, ModuleEntry};
-use moodle::data::course_traversal::get_course_info;
-use moodle::data::gen_module::GenModule;
-use moodle::Moodle;
+use moodle::data::course_contents::{Id, ModuleKey};
+use moodle::data::course_traversal::{get_course_info_many, CourseScan, ScanError};
+use moodle::data::gen_module::{EntryValue, GenModule};
+use moodle::data::icons;
+use moodle::data::labels::Locale;
+use moodle::{Moodle, MoodleError};
+
+use crate::moodle_stuff::accounts::CourseTarget;
+use crate::moodle_stuff::forum_mirroring::mirror_forums;
 
 /// Represents a moodle course as a discord message
-struct MoodleEmbed {
+pub(crate) struct MoodleEmbed {
     mod_icon_url: String,
     files: Vec<(String, Vec<u8>)>,
     color: u32,
     description: String,
     name: String,
     link: Option<String>,
+    completion: Option<(i64, i64)>, // (Course_id, Module_id) for the "Mark done" button
 }
 
 impl MoodleEmbed {
-    fn new(color: u32, mod_icon_url: String, name: String, link: Option<String>) -> MoodleEmbed {
+    pub(crate) fn new(
+        color: u32,
+        mod_icon_url: String,
+        name: String,
+        link: Option<String>,
+    ) -> MoodleEmbed {
         MoodleEmbed {
             mod_icon_url,
             files: vec![],
@@ -35,27 +48,29 @@ impl MoodleEmbed {
             description: "".into(),
             name,
             link,
+            completion: None,
         }
     }
 
     /// Create a discord embed that represents a new moodle module
-    async fn added(module: GenModule, client: &Moodle) -> MoodleEmbed {
+    async fn added(module: GenModule, client: &Moodle, locale: Locale) -> MoodleEmbed {
         let mut embed = MoodleEmbed::new(0x00FF00, module.mod_icon_url, module.name, module.link);
-        embed.add_raw_entries(module.entries);
+        embed.add_raw_entries(module.entries, locale);
         embed.add_files(module.files, client).await;
         embed
     }
 
-    fn add_raw_entries(&mut self, entries: IndexMap<String, String>) {
+    fn add_raw_entries(&mut self, entries: IndexMap<String, EntryValue>, locale: Locale) {
         for (key, val) in entries {
-            self.add_entry(format!("**{key}:** {val}"));
+            let key = locale.label(&key);
+            self.add_entry(format!("**{key}:** {}", render(&val, locale)));
         }
     }
 
     /// Create a discord embed that represents a removed moodle module
-    fn removed(module: GenModule) -> MoodleEmbed {
+    fn removed(module: GenModule, locale: Locale) -> MoodleEmbed {
         let mut embed = MoodleEmbed::new(0xFF0000, module.mod_icon_url, module.name, module.link);
-        embed.add_raw_entries(module.entries);
+        embed.add_raw_entries(module.entries, locale);
         for (key, _) in module.files {
             embed.add_entry(format!("__File:__ {key}"));
         }
@@ -63,24 +78,31 @@ impl MoodleEmbed {
     }
 
     /// download files
-    async fn add_files(&mut self, files: IndexMap<String, String>, client: &Moodle) {
+    pub(crate) async fn add_files(&mut self, files: IndexMap<String, String>, client: &Moodle) {
         for (name, url) in files {
             // download files under 8mb (max file size on discord)
-            let file = client.download_file(url, 8000000).await;
-            if let Ok(file) = file {
-                self.files.push((name, file));
-            } else {
-                self.add_entry(format!("__Added large file:__ {name}"));
+            match client.download_file(url, 8000000).await {
+                Ok(file) => self.files.push((name, file)),
+                Err(MoodleError::TooLarge { size, .. }) => {
+                    let megabytes = size as f64 / 1_000_000.0;
+                    self.add_entry(format!("__Added large file:__ {name} ({megabytes:.1} MB)"));
+                }
+                Err(why) => {
+                    println!("Failed to download {name}: {why}");
+                    self.add_entry(format!("__Added file (download failed):__ {name}"));
+                }
             }
         }
     }
 
     /// Create a discord embed that represents a modified moodle module
     async fn changed(
-        module_old: GenModule,
+        mut module_old: GenModule,
         module_new: GenModule,
         client: &Moodle,
+        locale: Locale,
     ) -> Option<MoodleEmbed> {
+        module_old.migrate_texts(&module_new);
         let mut embed = MoodleEmbed::new(
             0x0000FF,
             module_new.mod_icon_url,
@@ -90,10 +112,18 @@ impl MoodleEmbed {
         let description = diff_module_entries(module_old.entries, module_new.entries);
         // representation of module entries
         for (key, val) in description {
+            let key = locale.label(&key);
             let entry = match val {
-                ModuleEntry::Added(val) => format!("🟢 **{key}:** {val}"),
-                ModuleEntry::Removed(val) => format!("🔴 **{key}:** {val}"),
-                ModuleEntry::Changed(old, new) => format!("🔵**{key}**\n__From:__ {old}\n__To:__ {new}"),
+                ModuleEntry::Added(val) => format!("🟢 **{key}:** {}", render(&val, locale)),
+                ModuleEntry::Removed(val) => format!("🔴 **{key}:** {}", render(&val, locale)),
+                ModuleEntry::Changed(old, new) => {
+                    let (old, new) = (render(&old, locale), render(&new, locale));
+                    // e.g. sizes of old snapshots, that were only stored in megabytes
+                    if old == new {
+                        continue;
+                    }
+                    format!("🔵**{key}**\n__From:__ {old}\n__To:__ {new}")
+                }
             };
             embed.add_entry(entry);
         }
@@ -122,7 +152,7 @@ impl MoodleEmbed {
         }
     }
 
-    fn add_entry(&mut self, string: String) -> bool {
+    pub(crate) fn add_entry(&mut self, string: String) -> bool {
         // an entry value is capped at 1500 chars
         let slice = string.chars().take(1500);
         // if the embed is larger than 4000 chars then dont add new entries (discord limit)
@@ -143,29 +173,84 @@ impl MoodleEmbed {
     }
 }
 
+/// discord representation of a module entry
+fn render(value: &EntryValue, locale: Locale) -> String {
+    match value {
+        EntryValue::Timestamp(timestamp) => format!("<t:{timestamp}:F>"),
+        EntryValue::Duration(seconds) => {
+            let (hours, minutes, seconds) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+            let parts: Vec<String> = [(hours, "h"), (minutes, "min"), (seconds, "s")]
+                .into_iter()
+                .filter(|(amount, _)| *amount != 0)
+                .map(|(amount, unit)| format!("{amount} {unit}"))
+                .collect();
+            parts.join(" ")
+        }
+        EntryValue::Bytes(bytes) => format!("{:.2}MB", *bytes as f32 / (1 << 20) as f32),
+        EntryValue::Integer(num) => num.to_string(),
+        EntryValue::Bool(true) => locale.label("yes"),
+        EntryValue::Bool(false) => locale.label("no"),
+        EntryValue::Label(key) => locale.label(key),
+        EntryValue::Text(text) | EntryValue::Url(text) => text.clone(),
+    }
+}
+
 async fn get_changes(
     client: &Moodle,
-    course_id: i64,
+    mut scan: CourseScan,
     course_name: &str,
+    course_id: i64,
     guild_id: GuildId,
 ) -> anyhow::Result<Vec<MoodleEmbed>> {
-    let new_course = get_course_info(client, course_id).await?;
-    //save the the new state of the course and read the old one
-    let old_course = read_old_write_new_file(&new_course, &course_name, &guild_id).await?;
+    let old_course = read_old_file(course_name, &guild_id).await?;
+    // modules of a failed module type keep their old state instead of showing up as removed
+    let mut new_course = std::mem::take(&mut scan.modules);
+    let scanned: HashSet<ModuleKey> = new_course.iter().map(|module| module.get_id()).collect();
+    new_course.extend(
+        old_course
+            .iter()
+            .filter(|module| scan.is_missing(module.get_id()))
+            .filter(|module| !scanned.contains(&module.get_id()))
+            .cloned(),
+    );
+    write_new_file(&new_course, course_name, &guild_id).await;
     //figure out which modules are new, old or changed
     let mapped_modules = compare(old_course, new_course);
 
     let mut embeds = vec![];
     for module in mapped_modules.a {
-        let embed = MoodleEmbed::removed(module);
+        let embed = MoodleEmbed::removed(module, scan.locale);
         embeds.push(embed)
     }
+    // added and changed modules can get a "Mark done" button
+    let mut actionable = vec![];
     for module in mapped_modules.b {
-        let embed = MoodleEmbed::added(module, &client).await;
-        embeds.push(embed);
+        let key = module.get_id();
+        actionable.push((key, MoodleEmbed::added(module, client, scan.locale).await));
     }
     for (old_module, new_module) in mapped_modules.common {
-        if let Some(embed) = MoodleEmbed::changed(old_module, new_module, &client).await {
+        let key = new_module.get_id();
+        if let Some(embed) = MoodleEmbed::changed(old_module, new_module, client, scan.locale).await
+        {
+            actionable.push((key, embed));
+        }
+    }
+
+    if !actionable.is_empty() {
+        // courses without completion tracking answer with an error
+        let manual: HashSet<i64> = client
+            .get_activities_completion(course_id)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|status| status.is_manual())
+            .map(|status| status.module_id)
+            .collect();
+        for (key, mut embed) in actionable {
+            match key.module_id() {
+                Some(id) if manual.contains(&id) => embed.completion = Some((course_id, id)),
+                _ => {}
+            }
             embeds.push(embed);
         }
     }
@@ -173,21 +258,23 @@ async fn get_changes(
     Ok(embeds)
 }
 
-async fn read_old_write_new_file(
-    new_course: &Vec<GenModule>,
-    course_name: &str,
-    guild: &GuildId,
-) -> anyhow::Result<Vec<GenModule>> {
-    // read old course state
-    let file_path = format!("courses/{}/{}.json", guild, course_name);
+fn course_file(course_name: &str, guild: &GuildId) -> String {
+    format!("courses/{}/{}.json", guild, course_name)
+}
+
+async fn read_old_file(course_name: &str, guild: &GuildId) -> anyhow::Result<Vec<GenModule>> {
+    let file_path = course_file(course_name, guild);
     let file_path = Path::new(&file_path);
     fs::create_dir_all(file_path.parent().unwrap()).await?;
-    let old_data = fs::read_to_string(&file_path).await;
-    let old_data = match old_data {
+    let old_data = match fs::read_to_string(&file_path).await {
         Ok(old_data) => serde_json::from_str::<Vec<GenModule>>(&old_data)?,
         Err(_) => vec![],
     };
-    //write new course state
+    Ok(old_data)
+}
+
+async fn write_new_file(new_course: &Vec<GenModule>, course_name: &str, guild: &GuildId) {
+    let file_path = course_file(course_name, guild);
     if let Ok(new_course_string) = serde_json::to_string(new_course) {
         let byte_array = new_course_string.as_bytes();
         if let Ok(mut file) = File::create(&file_path).await {
@@ -196,8 +283,6 @@ async fn read_old_write_new_file(
             }
         }
     }
-
-    Ok(old_data)
 }
 
 async fn send_changes(
@@ -208,37 +293,68 @@ async fn send_changes(
 ) {
     for moodle_embed in embeds {
         for channel in channels {
-            if let Err(why) = channel
-                .send_message(http, |message| {
-                    message.add_embed(|embed| {
-                        embed
-                            .color(moodle_embed.color)
-                            .title(&moodle_embed.name)
-                            .footer(|a| a.text(&course_name))
-                            .description(&moodle_embed.description)
-                            .thumbnail(&moodle_embed.mod_icon_url);
-                        if let Some(link) = &moodle_embed.link {
-                            embed.url(link);
-                        }
-                        embed
-                    });
-
-                    for (name, data) in moodle_embed.files.iter() {
-                        message.add_file(AttachmentType::Bytes {
-                            data: Cow::from(data),
-                            filename: name.clone(),
-                        });
-                    }
-                    message
-                })
-                .await
-            {
+            if let Err(why) = send_embed(&moodle_embed, course_name, *channel, http).await {
                 println!("channel: {}, course: {}, {:#?}", channel, course_name, why);
             }
         }
     }
 }
 
+/// post a single embed together with its files
+pub(crate) async fn send_embed(
+    moodle_embed: &MoodleEmbed,
+    course_name: &str,
+    channel: ChannelId,
+    http: &Arc<Http>,
+) -> serenity::Result<Message> {
+    channel
+        .send_message(http, |message| {
+            message.add_embed(|embed| {
+                embed
+                    .color(moodle_embed.color)
+                    .title(&moodle_embed.name)
+                    .footer(|a| a.text(course_name))
+                    .description(&moodle_embed.description);
+                if !moodle_embed.mod_icon_url.is_empty() {
+                    embed.thumbnail(&moodle_embed.mod_icon_url);
+                }
+                if let Some(link) = &moodle_embed.link {
+                    embed.url(link);
+                }
+                embed
+            });
+
+            if let Some((course_id, module_id)) = moodle_embed.completion {
+                message.components(|components| {
+                    components.create_action_row(|row| {
+                        row.create_button(|button| {
+                            button
+                                .custom_id(format!("completion {course_id} {module_id}"))
+                                .label("Mark done")
+                                .style(ButtonStyle::Success)
+                        })
+                    })
+                });
+            }
+
+            // icons that come with the moodle crate are uploaded with the message
+            if let Some((filename, data)) = icons::bundled(&moodle_embed.mod_icon_url) {
+                message.add_file(AttachmentType::Bytes {
+                    data: Cow::from(data),
+                    filename: filename.to_string(),
+                });
+            }
+            for (name, data) in moodle_embed.files.iter() {
+                message.add_file(AttachmentType::Bytes {
+                    data: Cow::from(data),
+                    filename: name.clone(),
+                });
+            }
+            message
+        })
+        .await
+}
+
 pub async fn update_course(
     course_name: &str,
     course_id: i64,
@@ -246,14 +362,104 @@ pub async fn update_course(
     http: &Arc<Http>,
     guild: GuildId,
     client: &Moodle,
-) {
-    let embeds = get_changes(client, course_id, course_name, guild).await;
-    match embeds {
-        Ok(embeds) => {
-            send_changes(embeds, course_name, channels, http).await;
-        }
+    locale: Locale,
+) -> anyhow::Result<UpdateReport> {
+    update_courses(
+        &[(course_name, course_id, channels)],
+        http,
+        guild,
+        client,
+        locale,
+    )
+    .await
+}
+
+/// Problems with single courses of an update, the other courses were updated anyway
+#[derive(Default)]
+pub struct UpdateReport {
+    /// (course name, error) of courses whose changes couldnt be posted
+    pub failed: Vec<(String, anyhow::Error)>,
+    /// (course name, error) of module types that couldnt be scanned, they keep their old state
+    pub incomplete: Vec<(String, ScanError)>,
+}
+
+/// update several courses of the same account, every module type is only requested once.
+/// Only a failing course list is an error, everything else ends up in the report
+pub async fn update_courses(
+    courses: &[CourseTarget<'_>],
+    http: &Arc<Http>,
+    guild: GuildId,
+    client: &Moodle,
+    locale: Locale,
+) -> anyhow::Result<UpdateReport> {
+    let course_ids: Vec<i64> = courses.iter().map(|(_, id, _)| *id).collect();
+    let mut new_courses = match get_course_info_many(client, &course_ids, locale).await {
+        Ok(new_courses) => new_courses,
         Err(why) => {
-            println!("Failed to update course: {:?}", why);
+            println!("Failed to update courses: {:?}", why);
+            return Err(why.into());
+        }
+    };
+
+    // forums are mirrored on top of the module changes, a failure there doesnt stop the update
+    let mut forums = client
+        .get_forums_for_courses(&course_ids)
+        .await
+        .unwrap_or_else(|why| {
+            println!("Failed to get forums: {:?}", why);
+            Default::default()
+        });
+
+    let mut report = UpdateReport::default();
+    for (course_name, course_id, channels) in courses {
+        let scan = new_courses.remove(course_id).unwrap_or_default();
+        for error in &scan.errors {
+            println!(
+                "Failed to scan {} in {course_name}: {:?}",
+                error.module_type, error.error
+            );
+            report
+                .incomplete
+                .push((course_name.to_string(), error.clone()));
+        }
+        match get_changes(client, scan, course_name, *course_id, guild).await {
+            Ok(embeds) => send_changes(embeds, course_name, channels, http).await,
+            Err(why) => {
+                println!("Failed to update course {course_name}: {:?}", why);
+                report.failed.push((course_name.to_string(), why));
+            }
         }
+
+        let forum_ids: Vec<i64> = forums
+            .remove(course_id)
+            .unwrap_or_default()
+            .iter()
+            .map(|forum| forum.id)
+            .collect();
+        if let Err(why) =
+            mirror_forums(&forum_ids, client, course_name, channels, http, guild).await
+        {
+            println!("Failed to mirror forums of {course_name}: {:?}", why);
+        }
+    }
+    Ok(report)
+}
+
+/// A short explanation of a failed update that can be shown to discord users
+pub fn describe_update_error(why: &anyhow::Error) -> &'static str {
+    why.downcast_ref::<MoodleError>()
+        .and_then(describe_moodle_error)
+        .unwrap_or("Failed to update course")
+}
+
+/// like describe_update_error, for errors that come straight from moodle.
+/// None if there is nothing better to say than the error itself
+pub fn describe_moodle_error(why: &MoodleError) -> Option<&'static str> {
+    match why {
+        MoodleError::InvalidToken => Some("The moodle token expired, please /login again"),
+        MoodleError::Maintenance => Some("Moodle is in maintenance mode"),
+        MoodleError::AccessDenied { .. } => Some("Moodle denied access to this course"),
+        MoodleError::Network(_) => Some("Moodle is not reachable"),
+        _ => None,
     }
 }
@@ -0,0 +1,213 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serenity::http::Http;
+use serenity::model::id::ChannelId;
+use serenity::model::prelude::GuildId;
+use tokio::fs;
+
+use moodle::data::markdown::html_to_markdown;
+use moodle::data::other_content::discussion::{Discussion, ForumPost};
+use moodle::data::other_content::file::FileInfo;
+use moodle::Moodle;
+
+use crate::moodle_stuff::course_scanning::{send_embed, MoodleEmbed};
+
+/// only the latest discussions of a forum are checked for new posts
+const DISCUSSIONS_PER_FORUM: u32 = 10;
+const DISCUSSION_COLOR: u32 = 0xF98012;
+
+/// What was already posted to discord, stored next to the course state
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ForumState {
+    discussions: HashMap<i64, DiscussionState>, // (Discussion_id, State)
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DiscussionState {
+    threads: HashMap<ChannelId, ChannelId>, // (Channel, Thread with the replies)
+    last_post: i64,                         // creation time of the newest mirrored post
+}
+
+/// post new discussions of the forums and put their replies into a thread per discussion
+pub async fn mirror_forums(
+    forum_ids: &[i64],
+    client: &Moodle,
+    course_name: &str,
+    channels: &HashSet<ChannelId>,
+    http: &Arc<Http>,
+    guild: GuildId,
+) -> anyhow::Result<()> {
+    let file_path = format!("courses/{}/{}.forums.json", guild, course_name);
+    let file_path = Path::new(&file_path);
+    fs::create_dir_all(file_path.parent().unwrap()).await?;
+    // the first scan only remembers what is there, otherwise every old announcement would be posted
+    let (mut state, first_scan) = match fs::read_to_string(file_path).await {
+        Ok(state) => (serde_json::from_str::<ForumState>(&state)?, false),
+        Err(_) => (ForumState::default(), true),
+    };
+
+    let mut result = Ok(());
+    for forum_id in forum_ids {
+        let discussions = match client
+            .get_forum_discussions(*forum_id, 0, DISCUSSIONS_PER_FORUM)
+            .await
+        {
+            Ok(discussions) => discussions,
+            Err(why) => {
+                result = Err(why.into());
+                continue;
+            }
+        };
+        for discussion in discussions {
+            let known = state.discussions.remove(&discussion.id);
+            let mut discussion_state = match known {
+                Some(known) => known,
+                None if first_scan => DiscussionState {
+                    threads: HashMap::new(),
+                    last_post: discussion.modified,
+                },
+                None => {
+                    let mut new = DiscussionState {
+                        threads: HashMap::new(),
+                        last_post: discussion.created,
+                    };
+                    start_threads(&discussion, &mut new, client, course_name, channels, http).await;
+                    new
+                }
+            };
+            if discussion.modified > discussion_state.last_post {
+                if let Err(why) = mirror_replies(
+                    &discussion,
+                    &mut discussion_state,
+                    client,
+                    course_name,
+                    channels,
+                    http,
+                )
+                .await
+                {
+                    result = Err(why);
+                }
+            }
+            state.discussions.insert(discussion.id, discussion_state);
+        }
+    }
+
+    fs::write(file_path, serde_json::to_string(&state)?).await?;
+    result
+}
+
+/// post the replies that were written since the last scan into the threads of the discussion
+async fn mirror_replies(
+    discussion: &Discussion,
+    state: &mut DiscussionState,
+    client: &Moodle,
+    course_name: &str,
+    channels: &HashSet<ChannelId>,
+    http: &Arc<Http>,
+) -> anyhow::Result<()> {
+    let posts = client.get_discussion_posts(discussion.id).await?;
+    let last_post = state.last_post;
+    let new_posts = posts
+        .into_iter()
+        .filter(|post| post.created > last_post && post.id != discussion.first_post_id);
+
+    for post in new_posts {
+        // discussions from before the first scan dont have a thread yet
+        start_threads(discussion, state, client, course_name, channels, http).await;
+        let embed = post_embed(&post, client).await;
+        for thread in state.threads.values() {
+            if let Err(why) = send_embed(&embed, course_name, *thread, http).await {
+                println!("thread: {}, course: {}, {:#?}", thread, course_name, why);
+            }
+        }
+        state.last_post = state.last_post.max(post.created);
+    }
+    Ok(())
+}
+
+/// post the discussion in every channel that doesnt have a thread for it yet
+async fn start_threads(
+    discussion: &Discussion,
+    state: &mut DiscussionState,
+    client: &Moodle,
+    course_name: &str,
+    channels: &HashSet<ChannelId>,
+    http: &Arc<Http>,
+) {
+    let missing: Vec<_> = channels
+        .iter()
+        .filter(|channel| !state.threads.contains_key(channel))
+        .collect();
+    if missing.is_empty() {
+        return;
+    }
+
+    let embed = discussion_embed(discussion, client).await;
+    for channel in missing {
+        let message = match send_embed(&embed, course_name, *channel, http).await {
+            Ok(message) => message,
+            Err(why) => {
+                println!("channel: {}, course: {}, {:#?}", channel, course_name, why);
+                continue;
+            }
+        };
+        // thread names are limited to 100 characters
+        let name: String = discussion.subject.chars().take(100).collect();
+        match channel
+            .create_public_thread(http, message.id, |thread| thread.name(name))
+            .await
+        {
+            Ok(thread) => {
+                state.threads.insert(*channel, thread.id);
+            }
+            Err(why) => println!("channel: {}, course: {}, {:#?}", channel, course_name, why),
+        }
+    }
+}
+
+async fn discussion_embed(discussion: &Discussion, client: &Moodle) -> MoodleEmbed {
+    message_embed(
+        discussion.subject.clone(),
+        Some(discussion.link.clone()),
+        &discussion.author,
+        &discussion.message,
+        &discussion.attachments,
+        client,
+    )
+    .await
+}
+
+async fn post_embed(post: &ForumPost, client: &Moodle) -> MoodleEmbed {
+    message_embed(
+        post.subject.clone(),
+        post.link.clone(),
+        &post.author,
+        &post.message,
+        &post.attachments,
+        client,
+    )
+    .await
+}
+
+async fn message_embed(
+    subject: String,
+    link: Option<String>,
+    author: &str,
+    message: &str,
+    attachments: &[FileInfo],
+    client: &Moodle,
+) -> MoodleEmbed {
+    let mut embed = MoodleEmbed::new(DISCUSSION_COLOR, String::new(), subject, link);
+    embed.add_entry(format!("**From:** {author}"));
+    embed.add_entry(html_to_markdown(message));
+    let files = attachments
+        .iter()
+        .map(|file| (file.filename.clone(), file.fileurl.clone()))
+        .collect();
+    embed.add_files(files, client).await;
+    embed
+}
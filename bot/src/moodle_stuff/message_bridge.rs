@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serenity::client::Context;
+use serenity::http::Http;
+use serenity::model::channel::Message;
+use serenity::model::id::{GuildId, MessageId, UserId};
+
+use moodle::data::markdown::html_to_markdown;
+use moodle::data::other_content::messages::{ConversationMessage, Notification};
+use moodle::Moodle;
+
+use crate::moodle_stuff::accounts::AccountList;
+use crate::moodle_stuff::course_scanning::{send_embed, MoodleEmbed};
+
+const NOTIFICATION_LIMIT: u32 = 20;
+const CONVERSATION_LIMIT: u32 = 20;
+const MESSAGE_LIMIT: u32 = 20;
+/// oldest forwarded messages stop accepting replies once there are more
+const MAX_REPLY_TARGETS: usize = 500;
+const NOTIFICATION_COLOR: u32 = 0xF98012;
+const MESSAGE_COLOR: u32 = 0x1177D1;
+
+/// guards dm_replies.json, every guild writes to it
+static REPLY_FILE: Mutex<()> = Mutex::new(());
+
+/// Newest notification and message that were already forwarded, per account
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+struct LastSeen {
+    notification: i64,
+    message: i64,
+}
+
+/// Where a reply to a forwarded message has to go
+#[derive(Debug, Serialize, Deserialize)]
+struct ReplyTarget {
+    guild: GuildId,
+    account: String,
+    to_user: i64, // moodle user that wrote the forwarded message
+}
+
+/// forward new notifications and private messages of every owned account to the owner's dms
+pub async fn forward_messages(guild: GuildId, accounts: &AccountList, http: &Arc<Http>) {
+    let file_path = format!("data/{}/message_markers.json", guild);
+    let file_path = Path::new(&file_path);
+    let mut markers: HashMap<String, LastSeen> = fs::read_to_string(file_path)
+        .ok()
+        .and_then(|markers| serde_json::from_str(&markers).ok())
+        .unwrap_or_default();
+
+    for (account, client, owner) in accounts.get_owned_accounts() {
+        let known = markers.get(account).copied();
+        match forward_account(guild, account, client, owner, known, http).await {
+            Ok(last_seen) => {
+                markers.insert(account.into(), last_seen);
+            }
+            Err(why) => println!("Failed to forward messages of {account}: {:?}", why),
+        }
+    }
+    // accounts that were logged out dont need a marker anymore
+    let logged_in = accounts.get_accounts();
+    markers.retain(|account, _| logged_in.contains(&account));
+
+    if let Ok(markers) = serde_json::to_string(&markers) {
+        if let Err(why) = fs::write(file_path, markers) {
+            println!("Couldnt save to file {why}");
+        }
+    }
+}
+
+/// without a marker the account is new, then everything is only marked as seen
+async fn forward_account(
+    guild: GuildId,
+    account: &str,
+    client: &Moodle,
+    owner: UserId,
+    known: Option<LastSeen>,
+    http: &Arc<Http>,
+) -> anyhow::Result<LastSeen> {
+    let mut notifications = client.get_popup_notifications(NOTIFICATION_LIMIT).await?;
+    let conversations = client.get_conversations(CONVERSATION_LIMIT).await?;
+
+    let newest_notification = notifications
+        .iter()
+        .map(|notification| notification.id)
+        .max();
+    let newest_message = conversations
+        .iter()
+        .filter_map(|conversation| conversation.last_message_id)
+        .max();
+    let Some(mut last_seen) = known else {
+        return Ok(LastSeen {
+            notification: newest_notification.unwrap_or_default(),
+            message: newest_message.unwrap_or_default(),
+        });
+    };
+
+    let channel = owner.create_dm_channel(http).await?;
+
+    notifications.retain(|notification| notification.id > last_seen.notification);
+    notifications.sort_by_key(|notification| notification.id);
+    for notification in notifications {
+        // the next scan tries again from here, e.g. when the owner doesnt accept dms
+        if let Err(why) = send_embed(
+            &notification_embed(&notification),
+            account,
+            channel.id,
+            http,
+        )
+        .await
+        {
+            println!("Cannot forward notification to {owner}: {why}");
+            return Ok(last_seen);
+        }
+        last_seen.notification = notification.id;
+    }
+
+    let user_id = client.site_info().await?.userid;
+    let mut messages = vec![];
+    for conversation in conversations {
+        if conversation.last_message_id.unwrap_or_default() <= last_seen.message {
+            continue;
+        }
+        messages.extend(
+            client
+                .get_conversation_messages(conversation.id, MESSAGE_LIMIT)
+                .await?
+                .into_iter()
+                .filter(|message| message.id > last_seen.message),
+        );
+    }
+    messages.sort_by_key(|message| message.id);
+    for message in messages {
+        // own messages are only written from discord or moodle itself
+        if message.author_id != user_id {
+            let sent = match send_embed(&message_embed(&message), account, channel.id, http).await {
+                Ok(sent) => sent,
+                Err(why) => {
+                    println!("Cannot forward message to {owner}: {why}");
+                    return Ok(last_seen);
+                }
+            };
+            save_reply_target(
+                sent.id,
+                ReplyTarget {
+                    guild,
+                    account: account.into(),
+                    to_user: message.author_id,
+                },
+            );
+        }
+        last_seen.message = message.id;
+    }
+    Ok(last_seen)
+}
+
+fn notification_embed(notification: &Notification) -> MoodleEmbed {
+    let mut embed = MoodleEmbed::new(
+        NOTIFICATION_COLOR,
+        String::new(),
+        notification.subject.clone(),
+        notification.url.clone(),
+    );
+    embed.add_entry(html_to_markdown(&notification.message));
+    embed
+}
+
+fn message_embed(message: &ConversationMessage) -> MoodleEmbed {
+    let author = message.author.as_deref().unwrap_or("Unknown");
+    let mut embed = MoodleEmbed::new(
+        MESSAGE_COLOR,
+        String::new(),
+        format!("Message from {author}"),
+        None,
+    );
+    embed.add_entry(html_to_markdown(&message.text));
+    embed.add_entry("*Reply to this message to answer it in moodle*".into());
+    embed
+}
+
+fn reply_file() -> &'static Path {
+    Path::new("data/dm_replies.json")
+}
+
+fn read_reply_targets() -> IndexMap<MessageId, ReplyTarget> {
+    fs::read_to_string(reply_file())
+        .ok()
+        .and_then(|targets| serde_json::from_str(&targets).ok())
+        .unwrap_or_default()
+}
+
+fn save_reply_target(message: MessageId, target: ReplyTarget) {
+    let _lock = REPLY_FILE.lock().unwrap();
+    let mut targets = read_reply_targets();
+    targets.insert(message, target);
+    while targets.len() > MAX_REPLY_TARGETS {
+        targets.shift_remove_index(0);
+    }
+    fs::create_dir_all(reply_file().parent().unwrap()).unwrap();
+    if let Ok(targets) = serde_json::to_string(&targets) {
+        if let Err(why) = fs::write(reply_file(), targets) {
+            println!("Couldnt save to file {why}");
+        }
+    }
+}
+
+/// send a dm that replies to a forwarded message back to moodle
+pub async fn answer_reply(ctx: &Context, msg: &Message) {
+    let Some(referenced) = &msg.referenced_message else {
+        return;
+    };
+    let target = {
+        let _lock = REPLY_FILE.lock().unwrap();
+        read_reply_targets().remove(&referenced.id)
+    };
+    let Some(target) = target else {
+        return;
+    };
+
+    let accounts = AccountList::get_from_file(target.guild);
+    let answer = match accounts.get_own_account(&target.account, msg.author.id) {
+        None => "This moodle account was logged out".to_string(),
+        Some(client) => match client
+            .send_instant_message(target.to_user, &msg.content)
+            .await
+        {
+            Ok(_) => "Sent to moodle ✅".to_string(),
+            Err(why) => format!("Couldnt send the message to moodle: {why}"),
+        },
+    };
+    if let Err(why) = msg.reply(&ctx.http, answer).await {
+        println!("Cannot respond to dm: {why}");
+    }
+}
@@ -1,2 +1,4 @@
 pub mod accounts;
 pub mod course_scanning;
+pub mod forum_mirroring;
+pub mod message_bridge;
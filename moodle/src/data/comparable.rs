@@ -35,16 +35,16 @@ pub fn compare<A: Id, B: Id>(a: Vec<A>, mut b: Vec<B>) -> Comparison<A, B> {
     comp
 }
 
-pub enum ModuleEntry {
-    Added(String),
-    Removed(String),
-    Changed(String, String), // old, new
+pub enum ModuleEntry<V = String> {
+    Added(V),
+    Removed(V),
+    Changed(V, V), // old, new
 }
 
-pub fn diff_module_entries(
-    mut old: IndexMap<String, String>,
-    new: IndexMap<String, String>,
-) -> IndexMap<String, ModuleEntry> {
+pub fn diff_module_entries<V: PartialEq>(
+    mut old: IndexMap<String, V>,
+    new: IndexMap<String, V>,
+) -> IndexMap<String, ModuleEntry<V>> {
     let mut mapped = IndexMap::new();
 
     for (new_key, new_val) in new {
@@ -1,43 +1,152 @@
-use crate::data::comparable::compare;
-use crate::data::gen_module::{GenModule, GenModuleBuilder};
-use crate::data::modules::unknown::CourseModule;
-use crate::Moodle;
-use anyhow::Result;
+use crate::data::gen_module::{Fallback, GenModule, GenModuleBuilder};
+use crate::{Moodle, MoodleError};
 use async_trait::async_trait;
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+pub use crate::data::modules::unknown::{Content, CourseModule, Date};
+
+/// Identity of a generated module. Sections and course modules have their own ids in moodle,
+/// which may overlap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "id", rename_all = "snake_case")]
+pub enum ModuleKey {
+    /// a course module, e.g. a quiz
+    Module(i64),
+    Section(i64),
+}
+
+impl ModuleKey {
+    /// the course module id, used by functions like the activity completion
+    pub fn module_id(self) -> Option<i64> {
+        match self {
+            ModuleKey::Module(id) => Some(id),
+            ModuleKey::Section(_) => None,
+        }
+    }
+}
 
 pub trait Id {
-    fn get_id(&self) -> i64;
+    fn get_id(&self) -> ModuleKey;
+}
+
+/// The generated modules of one module type in one course
+#[derive(Debug, Default)]
+pub struct Generated {
+    pub modules: Vec<GenModule>,
+    /// course modules whose details couldnt be requested, they are missing from `modules`
+    pub failed: Vec<(i64, Arc<MoodleError>)>,
+}
+
+/// A failed detail request of a single module, see request_details.
+/// Only equal to itself, as errors cant be compared
+#[derive(Debug, Clone)]
+pub struct DetailError(pub Arc<MoodleError>);
+
+impl PartialEq for DetailError {
+    fn eq(&self, other: &DetailError) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
 }
 
 /// Defines how moodle modules should be captured
 #[async_trait]
-pub trait Generate: Id + Sized {
+pub trait Generate: Id + Sized + Send {
+    /// the web service function that request_many relies on
+    const FUNCTION: &'static str;
+
     /// send a request to fetch all modules of a certain type in a moodle course
-    async fn request(client: &Moodle, id: i64) -> Result<Vec<Self>>;
+    async fn request(client: &Moodle, id: i64) -> Result<Vec<Self>, MoodleError> {
+        let mut modules = Self::request_many(client, &[id]).await?;
+        Ok(modules.remove(&id).unwrap_or_default())
+    }
+
+    /// fetch all modules of a certain type in several courses with a single request
+    async fn request_many(
+        client: &Moodle,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Self>>, MoodleError>;
 
     /// Generate Modules from the corresponding course-module and a more specific request for the exact module type
     async fn process(
         course_modules: Vec<CourseModule>,
         client: &Moodle,
         course_id: i64,
-    ) -> Result<Vec<GenModule>> {
-        let mapped_modules = Self::request(client, course_id).await?;
-        let res = merge(course_modules, mapped_modules) //think about non 1:1 cases
+    ) -> Result<Generated, MoodleError> {
+        let course_modules = HashMap::from([(course_id, course_modules)]);
+        let mut generated = Self::process_many(course_modules, client).await?;
+        Ok(generated.remove(&course_id).unwrap_or_default())
+    }
+
+    /// Like process, but for the modules of several courses (course id -> course modules)
+    async fn process_many(
+        course_modules: HashMap<i64, Vec<CourseModule>>,
+        client: &Moodle,
+    ) -> Result<HashMap<i64, Generated>, MoodleError> {
+        let mut course_ids: Vec<i64> = course_modules.keys().copied().collect();
+        course_ids.sort();
+        let mut mapped_modules = Self::request_many(client, &course_ids).await?;
+        Self::add_details(&mut mapped_modules, &course_modules, client).await;
+        let res = course_modules
             .into_iter()
-            .map(|(course_module, mapped_module)| {
-                let mut builder = GenModuleBuilder::new(
-                    course_module.id,
-                    course_module.modicon.clone(),
-                    course_module.name.clone(),
-                    course_module.url.clone(),
-                );
-                mapped_module.gen(&mut builder, course_module);
-                builder.build()
+            .map(|(course_id, course_modules)| {
+                let mapped = mapped_modules.remove(&course_id).unwrap_or_default();
+                (course_id, Self::build(course_modules, mapped))
             })
             .collect();
         Ok(res)
     }
 
+    /// fill in details that are only known with the course contents, e.g. the chapter texts of
+    /// books. Like request_details, a failure should only affect its module
+    async fn add_details(
+        _modules: &mut HashMap<i64, Vec<Self>>,
+        _course_modules: &HashMap<i64, Vec<CourseModule>>,
+        _client: &Moodle,
+    ) {
+    }
+
+    /// combine the course-modules with the fetched modules of one course. Course-modules without a
+    /// fetched module keep the generic information of the course contents, modules whose details
+    /// failed are left out
+    fn build(course_modules: Vec<CourseModule>, mut mapped_modules: Vec<Self>) -> Generated {
+        let mut generated = Generated::default();
+        for course_module in course_modules {
+            let position = mapped_modules
+                .iter()
+                .position(|mapped_module| mapped_module.get_id() == course_module.get_id());
+            let Some(position) = position else {
+                generated
+                    .modules
+                    .push(course_module.fallback(Fallback::NotReturned));
+                continue;
+            };
+            let mapped_module = mapped_modules.swap_remove(position);
+            if let Some(why) = mapped_module.failure() {
+                generated.failed.push((course_module.id, why));
+                continue;
+            }
+            let mut builder = GenModuleBuilder::new(
+                course_module.get_id(),
+                course_module.modicon.clone(),
+                course_module.name.clone(),
+                course_module.url.clone(),
+            );
+            mapped_module.gen(&mut builder, course_module);
+            generated.modules.push(builder.build());
+        }
+        generated
+    }
+
+    /// set if the details of this module couldnt be requested. The module is left out of the
+    /// scan, so it keeps its previous state instead of losing the details
+    fn failure(&self) -> Option<Arc<MoodleError>> {
+        None
+    }
+
     /// serialize the module entries
     fn gen(
         self,
@@ -46,7 +155,47 @@ pub trait Generate: Id + Sized {
     ) -> &mut GenModuleBuilder;
 }
 
-/// find all pairs of elements in a and b with the same id
-pub fn merge<A: Id, B: Id>(a: Vec<A>, b: Vec<B>) -> Vec<(A, B)> {
-    compare(a, b).common
+/// Request the details of single modules for request_many, e.g. the submission status of every
+/// assignment. Nothing is requested if the service doesnt allow `function`, modules the account
+/// cant access have no details. Other failures only affect their module
+pub(crate) async fn request_details<T, F, Fut>(
+    client: &Moodle,
+    function: &str,
+    ids: Vec<i64>,
+    request: F,
+) -> HashMap<i64, Result<T, DetailError>>
+where
+    F: Fn(i64) -> Fut,
+    Fut: Future<Output = Result<T, MoodleError>>,
+{
+    let allowed = match client.site_info().await {
+        Ok(site_info) => site_info.allows(function),
+        Err(_) => true,
+    };
+    if !allowed {
+        return HashMap::new();
+    }
+
+    let results: Vec<(i64, Result<T, MoodleError>)> = stream::iter(ids)
+        .map(|id| {
+            let details = request(id);
+            async move { (id, details.await) }
+        })
+        .buffered(client.concurrency())
+        .collect()
+        .await;
+    let mut details = HashMap::new();
+    for (id, result) in results {
+        match result {
+            Ok(result) => {
+                details.insert(id, Ok(result));
+            }
+            // e.g. an individual wiki of another group or a teacher without submissions
+            Err(MoodleError::AccessDenied { .. } | MoodleError::ModuleUnavailable { .. }) => {}
+            Err(why) => {
+                details.insert(id, Err(DetailError(Arc::new(why))));
+            }
+        }
+    }
+    details
 }
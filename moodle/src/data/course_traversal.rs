@@ -1,72 +1,207 @@
-use crate::data::course_contents::Generate;
+use crate::data::course_contents::ModuleKey;
 use crate::data::gen_module::GenModule;
-use crate::data::modules::assignment::Assignment;
-use crate::data::modules::bigbluebutton::Bigbluebuttonbn;
-use crate::data::modules::chat::Chat;
-use crate::data::modules::choice::Choice;
-use crate::data::modules::feedback::Feedback;
-use crate::data::modules::folder::Folder;
-use crate::data::modules::forum::Forum;
-use crate::data::modules::glossary::Glossary;
-use crate::data::modules::label::Label;
-use crate::data::modules::page::Page;
-use crate::data::modules::quiz::Quiz;
-use crate::data::modules::resource::Resource;
+use crate::data::labels::Locale;
+use crate::data::lenient::ParseWarning;
 use crate::data::modules::unknown::CourseModule;
-use crate::data::modules::url::Url;
-use crate::Moodle;
-use anyhow::Result;
-use std::collections::HashMap;
+use crate::data::other_content::section::Course;
+use crate::data::registry::ModuleRegistry;
+use crate::{Moodle, MoodleError};
+use futures::{stream, StreamExt, TryStreamExt};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
 
-/// public interface to generate course info
-pub async fn get_course_info(client: &Moodle, course_id: i64) -> Result<Vec<GenModule>> {
-    let course = client.get_course_contents(course_id).await?;
-    let mut sections = vec![];
-    let mut grouped_modules = HashMap::new();
+/// The generated modules of a course. Module types that failed are missing and listed in `errors`
+#[derive(Debug, Default)]
+pub struct CourseScan {
+    pub modules: Vec<GenModule>,
+    pub errors: Vec<ScanError>,
+    /// language the entries should be shown in, the modules only contain machine keys
+    pub locale: Locale,
+}
 
-    // fill up sections and grouped_modules with the information of course
-    for section in course {
-        sections.push(section.section_info);
+/// Modules of a course that couldnt be generated, a whole module type or a single module
+/// whose details failed
+#[derive(Debug, Clone)]
+pub struct ScanError {
+    /// modname, e.g. "quiz"
+    pub module_type: String,
+    /// ids of the course modules that are missing from the scan
+    pub module_ids: Vec<i64>,
+    /// shared by every course of the failed request
+    pub error: Arc<MoodleError>,
+}
 
-        for module in section.modules {
-            grouped_modules
-                .entry(module.modname.clone())
-                .or_insert(vec![])
-                .push(module);
+impl CourseScan {
+    fn new(locale: Locale) -> CourseScan {
+        CourseScan {
+            locale,
+            ..CourseScan::default()
         }
     }
 
-    let mut gen_modules = vec![];
-    for (typ, module_group) in grouped_modules {
-        // collect more in depth information that is module group specific, also do serialisation
-        gen_modules.extend(match_type(typ.as_str(), module_group, client, course_id).await?);
+    /// label of an entry key in the language the scan was requested in
+    pub fn label(&self, key: &str) -> String {
+        self.locale.label(key)
     }
 
-    // sections only need to be serialized
-    gen_modules.extend(sections.into_iter().map(|section| section.process()));
-    Ok(gen_modules)
+    /// true if the module couldnt be generated, its previous state is still valid
+    pub fn is_missing(&self, key: ModuleKey) -> bool {
+        let Some(id) = key.module_id() else {
+            return false;
+        };
+        self.errors
+            .iter()
+            .any(|error| error.module_ids.contains(&id))
+    }
 }
 
-async fn match_type(
-    name: &str,
-    course_modules: Vec<CourseModule>,
+/// public interface to generate course info with the module types of this crate
+pub async fn get_course_info(
     client: &Moodle,
     course_id: i64,
-) -> Result<Vec<GenModule>> {
-    match name {
-        "assign" => Assignment::process(course_modules, client, course_id).await,
-        "bigbluebuttonbn" => Bigbluebuttonbn::process(course_modules, client, course_id).await,
-        "chat" => Chat::process(course_modules, client, course_id).await,
-        "choice" | "questionnaire" => Choice::process(course_modules, client, course_id).await,
-        "feedback" => Feedback::process(course_modules, client, course_id).await,
-        "folder" => Folder::process(course_modules, client, course_id).await,
-        "forum" => Forum::process(course_modules, client, course_id).await,
-        "glossary" => Glossary::process(course_modules, client, course_id).await,
-        "label" => Label::process(course_modules, client, course_id).await,
-        "page" => Page::process(course_modules, client, course_id).await,
-        "quiz" => Quiz::process(course_modules, client, course_id).await,
-        "resource" => Resource::process(course_modules, client, course_id).await,
-        "url" => Url::process(course_modules, client, course_id).await,
-        _ => CourseModule::process(course_modules, client, course_id).await,
+    locale: Locale,
+) -> Result<CourseScan, MoodleError> {
+    ModuleRegistry::default()
+        .get_course_info(client, course_id, locale)
+        .await
+}
+
+/// generate the info of several courses with the module types of this crate
+pub async fn get_course_info_many(
+    client: &Moodle,
+    course_ids: &[i64],
+    locale: Locale,
+) -> Result<HashMap<i64, CourseScan>, MoodleError> {
+    ModuleRegistry::default()
+        .get_course_info_many(client, course_ids, locale)
+        .await
+}
+
+impl ModuleRegistry {
+    /// generate course info, module types without a handler use the course contents
+    pub async fn get_course_info(
+        &self,
+        client: &Moodle,
+        course_id: i64,
+        locale: Locale,
+    ) -> Result<CourseScan, MoodleError> {
+        let mut courses = self
+            .get_course_info_many(client, &[course_id], locale)
+            .await?;
+        Ok(courses
+            .remove(&course_id)
+            .unwrap_or_else(|| CourseScan::new(locale)))
+    }
+
+    /// generate the info of several courses at once, every module type is only requested once.
+    /// Only failing course contents are an error, a failing module type is reported in the scan
+    pub async fn get_course_info_many(
+        &self,
+        client: &Moodle,
+        course_ids: &[i64],
+        locale: Locale,
+    ) -> Result<HashMap<i64, CourseScan>, MoodleError> {
+        // without site info every module function is assumed to be allowed
+        let site_info = match client.site_info().await {
+            Ok(site_info) => Some(site_info),
+            Err(MoodleError::AccessDenied { .. }) => None,
+            Err(why) => {
+                client.warn(ParseWarning {
+                    function: "core_webservice_get_site_info".into(),
+                    item: "SiteInfo",
+                    index: 0,
+                    message: why.to_string(),
+                });
+                None
+            }
+        };
+        let courses: Vec<Course> = stream::iter(course_ids.to_vec())
+            .map(|course_id| client.get_course_contents(course_id))
+            .buffered(client.concurrency())
+            .try_collect()
+            .await?;
+        let mut sections = HashMap::new();
+        // sorted by module type, so the generated modules always have the same order
+        let mut grouped_modules: BTreeMap<String, HashMap<i64, Vec<CourseModule>>> =
+            BTreeMap::new();
+
+        // fill up sections and grouped_modules with the information of each course
+        for (course_id, course) in course_ids.iter().zip(courses) {
+            let course_sections = sections.entry(*course_id).or_insert(vec![]);
+            for section in course {
+                course_sections.push(section.section_info);
+
+                for module in section.modules {
+                    grouped_modules
+                        .entry(module.modname.clone())
+                        .or_default()
+                        .entry(*course_id)
+                        .or_default()
+                        .push(module);
+                }
+            }
+        }
+
+        // collect more in depth information that is module group specific, also do serialisation.
+        // buffered keeps the order of the groups, the client limits how many requests are running
+        let generated: Vec<_> = stream::iter(grouped_modules)
+            .map(|(typ, module_group)| async move {
+                // remember which modules go missing if the module type fails
+                let ids: HashMap<i64, Vec<i64>> = module_group
+                    .iter()
+                    .map(|(course_id, modules)| {
+                        (*course_id, modules.iter().map(|module| module.id).collect())
+                    })
+                    .collect();
+                let res = self
+                    .process(typ.as_str(), module_group, client, site_info)
+                    .await;
+                (typ, ids, res)
+            })
+            .buffered(client.concurrency())
+            .collect()
+            .await;
+
+        let mut scans: HashMap<i64, CourseScan> = course_ids
+            .iter()
+            .map(|id| (*id, CourseScan::new(locale)))
+            .collect();
+        for (typ, ids, res) in generated {
+            match res {
+                Ok(group) => {
+                    for (course_id, generated) in group {
+                        let scan = scans.entry(course_id).or_default();
+                        scan.modules.extend(generated.modules);
+                        for (module_id, error) in generated.failed {
+                            scan.errors.push(ScanError {
+                                module_type: typ.clone(),
+                                module_ids: vec![module_id],
+                                error,
+                            });
+                        }
+                    }
+                }
+                Err(why) => {
+                    let error = Arc::new(why);
+                    for (course_id, module_ids) in ids {
+                        scans.entry(course_id).or_default().errors.push(ScanError {
+                            module_type: typ.clone(),
+                            module_ids,
+                            error: error.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        // sections only need to be serialized
+        for (course_id, course_sections) in sections {
+            scans
+                .entry(course_id)
+                .or_default()
+                .modules
+                .extend(course_sections.into_iter().map(|section| section.process()));
+        }
+        Ok(scans)
     }
 }
@@ -1,33 +1,228 @@
-use crate::data::course_contents::Id;
+use crate::data::course_contents::{Id, ModuleKey};
+use crate::data::markdown::{html_to_markdown, html_to_text};
 use crate::data::modules::unknown::Content;
 use crate::data::other_content::file::FileInfo;
+use crate::data::{icons, labels};
 use indexmap::IndexMap;
 use regex::Regex;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::cmp::max;
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// key of the quiz and lesson time limit
+const TIME_LIMIT: &str = "time_limit";
+/// older snapshots stored the time limit as plain seconds under this key
+const LEGACY_TIME_LIMIT: &str = "Zeitspanne in Sekunden";
+/// key of the chat start time, older snapshots stored it as a plain number
+const CHAT_TIME: &str = "chat_time";
+/// older snapshots stored sections with their id multiplied by this
+const LEGACY_SECTION_FACTOR: i64 = 10000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "StoredModule")]
 pub struct GenModule {
-    pub entries: IndexMap<String, String>,
+    pub entries: IndexMap<String, EntryValue>,
     pub files: IndexMap<String, String>,
     pub mod_icon_url: String,
     pub name: String,
     pub link: Option<String>,
-    id: i64,
+    id: ModuleKey,
+    /// set if the module was generated from the course contents alone
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<Fallback>,
+    /// the texts like snapshots from before markdown stored them, only set for new modules
+    #[serde(skip)]
+    legacy_texts: IndexMap<String, String>,
+}
+
+impl GenModule {
+    /// Snapshots from before descriptions were converted to markdown contain the text with
+    /// its html stripped. Such texts take the markdown of `new` if nothing but the conversion
+    /// changed, otherwise every formatted description would show up as changed
+    pub fn migrate_texts(&mut self, new: &GenModule) {
+        for (key, value) in self.entries.iter_mut() {
+            let EntryValue::Text(old) = value else {
+                continue;
+            };
+            if new.legacy_texts.get(key) == Some(old) {
+                if let Some(text) = new.entries.get(key) {
+                    *value = text.clone();
+                }
+            }
+        }
+    }
+}
+
+/// Why a module only has the generic information of the course contents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fallback {
+    /// there is no specific request for this module type
+    UnknownType,
+    /// the token isnt allowed to use the function of this module type
+    NotInService,
+    /// the module type was requested but the module wasnt in the answer, e.g. hidden or restricted modules
+    NotReturned,
+}
+
+/// A captured value, rendering it is up to the consumer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum EntryValue {
+    /// unix timestamp in seconds
+    Timestamp(i64),
+    /// in seconds
+    Duration(i64),
+    Bytes(i64),
+    Integer(i64),
+    Bool(bool),
+    Text(String),
+    Url(String),
+    /// machine key of a text that is translated when it is shown, see `labels`
+    Label(String),
+}
+
+impl EntryValue {
+    /// read a value that was rendered for discord before entries were typed
+    pub fn from_legacy(value: &str) -> EntryValue {
+        let timestamp = value
+            .strip_prefix("<t:")
+            .and_then(|value| value.strip_suffix(":F>"))
+            .and_then(|value| value.parse().ok());
+        if let Some(timestamp) = timestamp {
+            return EntryValue::Timestamp(timestamp);
+        }
+        let megabytes = value
+            .strip_suffix("MB")
+            .and_then(|value| value.parse::<f64>().ok());
+        if let Some(megabytes) = megabytes {
+            return EntryValue::Bytes((megabytes * (1 << 20) as f64).round() as i64);
+        }
+        if let Ok(num) = value.parse() {
+            return EntryValue::Integer(num);
+        }
+        match value {
+            "Ja" => EntryValue::Bool(true),
+            "Nein" => EntryValue::Bool(false),
+            _ if value.starts_with("https://") || value.starts_with("http://") => {
+                EntryValue::Url(value.to_string())
+            }
+            _ => EntryValue::Text(value.to_string()),
+        }
+    }
+}
+
+/// A module as it is stored in a snapshot, older snapshots are migrated when they are loaded
+#[derive(Deserialize)]
+struct StoredModule {
+    #[serde(deserialize_with = "entries_compat")]
+    entries: IndexMap<String, EntryValue>,
+    files: IndexMap<String, String>,
+    mod_icon_url: String,
+    name: String,
+    link: Option<String>,
+    id: StoredKey,
+    #[serde(default)]
+    fallback: Option<Fallback>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredKey {
+    Typed(ModuleKey),
+    /// course module id, or the section id * 10000 for sections
+    Legacy(i64),
+}
+
+impl From<StoredModule> for GenModule {
+    fn from(stored: StoredModule) -> GenModule {
+        let (id, mod_icon_url) = match stored.id {
+            StoredKey::Typed(key) => (key, stored.mod_icon_url),
+            // the multiplied id alone is ambiguous, but only sections had the discord icon
+            StoredKey::Legacy(id) if stored.mod_icon_url == icons::LEGACY_SECTION_ICON_URL => (
+                ModuleKey::Section(id / LEGACY_SECTION_FACTOR),
+                icons::SECTION_ICON_URL.to_string(),
+            ),
+            StoredKey::Legacy(id) => (ModuleKey::Module(id), stored.mod_icon_url),
+        };
+        GenModule {
+            entries: stored.entries,
+            files: stored.files,
+            mod_icon_url,
+            name: stored.name,
+            link: stored.link,
+            id,
+            fallback: stored.fallback,
+            legacy_texts: IndexMap::new(),
+        }
+    }
+}
+
+/// snapshots from before typed entries only contain strings and snapshots from before machine
+/// keys use German keys, both are converted on load
+fn entries_compat<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<IndexMap<String, EntryValue>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Stored {
+        Typed(EntryValue),
+        Legacy(String),
+    }
+
+    let stored = IndexMap::<String, Stored>::deserialize(deserializer)?;
+    let entries = stored
+        .into_iter()
+        .filter_map(|(key, value)| match value {
+            Stored::Typed(value) => machine_entry(key, value),
+            // the time limit used to be a plain number, 0 meant no limit
+            Stored::Legacy(value) if key == LEGACY_TIME_LIMIT => match value.parse() {
+                Ok(0) => None,
+                Ok(seconds) => Some((TIME_LIMIT.to_string(), EntryValue::Duration(seconds))),
+                Err(_) => Some((key, EntryValue::Text(value))),
+            },
+            Stored::Legacy(value) => machine_entry(key, EntryValue::from_legacy(&value)),
+        })
+        .collect();
+    Ok(entries)
+}
+
+fn machine_entry(key: String, value: EntryValue) -> Option<(String, EntryValue)> {
+    let key = labels::legacy_key(&key).unwrap_or(key);
+    let value = match value {
+        EntryValue::Text(text) => match labels::legacy_value(&key, &text) {
+            Some(label) => EntryValue::Label(label),
+            None => EntryValue::Text(text),
+        },
+        // like GenModuleBuilder::date, a chat without a start time has no entry
+        EntryValue::Integer(0) if key == CHAT_TIME => return None,
+        EntryValue::Integer(time) if key == CHAT_TIME => EntryValue::Timestamp(time),
+        value => value,
+    };
+    Some((key, value))
 }
 
 impl Id for GenModule {
-    fn get_id(&self) -> i64 {
+    fn get_id(&self) -> ModuleKey {
         self.id
     }
 }
 
+/// How GenModuleBuilder::string converts html
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextMode {
+    /// discord markdown, see html_to_markdown
+    #[default]
+    Markdown,
+    /// text without any markdown, see html_to_text
+    Plain,
+}
+
 /// Serialize module entries
-pub struct GenModuleBuilder(GenModule);
+pub struct GenModuleBuilder(GenModule, TextMode);
 
 impl GenModuleBuilder {
     pub fn new(
-        id: i64,
+        id: ModuleKey,
         mod_icon_url: String,
         name: String,
         link: Option<String>,
@@ -39,19 +234,30 @@ impl GenModuleBuilder {
             link,
             name,
             id,
+            fallback: None,
+            legacy_texts: IndexMap::new(),
         };
-        GenModuleBuilder(module)
+        GenModuleBuilder(module, TextMode::default())
     }
 
     pub fn build(self) -> GenModule {
         self.0
     }
 
+    /// how the following string calls convert html
+    pub fn text_mode(&mut self, mode: TextMode) -> &mut Self {
+        self.1 = mode;
+        self
+    }
+
+    pub fn fallback(&mut self, reason: Fallback) -> &mut Self {
+        self.0.fallback = Some(reason);
+        self
+    }
+
     pub fn date(&mut self, name: &str, date: i64) -> &mut Self {
         if date != 0 {
-            self.0
-                .entries
-                .insert(name.to_string(), format!("<t:{:?}:F>", date));
+            self.insert(name, EntryValue::Timestamp(date));
         }
         self
     }
@@ -63,30 +269,40 @@ impl GenModuleBuilder {
         self
     }
 
-    pub fn num(&mut self, name: &str, num: i64) -> &mut Self {
-        self.0.entries.insert(name.to_string(), num.to_string());
+    /// 0 means there is no time limit
+    pub fn time_limit(&mut self, seconds: i64) -> &mut Self {
+        if seconds != 0 {
+            self.insert(TIME_LIMIT, EntryValue::Duration(seconds));
+        }
         self
     }
 
-    pub fn megabytes(&mut self, name: &str, bytes: i64) -> &mut Self {
-        let mb = bytes as f32 / (1 << 20) as f32;
-        let s = format!("{:.2}MB", mb);
-        self.0.entries.insert(name.to_string(), s);
-        self
+    pub fn num(&mut self, name: &str, num: i64) -> &mut Self {
+        self.insert(name, EntryValue::Integer(num))
+    }
+
+    pub fn bytes(&mut self, name: &str, bytes: i64) -> &mut Self {
+        self.insert(name, EntryValue::Bytes(bytes))
     }
 
-    pub fn megabytes_option(&mut self, name: &str, bytes: Option<i64>) -> &mut Self {
+    pub fn bytes_option(&mut self, name: &str, bytes: Option<i64>) -> &mut Self {
         if let Some(bytes) = bytes {
-            self.megabytes(name, bytes);
+            self.bytes(name, bytes);
         }
         self
     }
 
+    /// html is converted to discord markdown, or to plain text in TextMode::Plain
     pub fn string(&mut self, name: &str, string: String) -> &mut Self {
-        if !string.is_empty() {
+        let text = match self.1 {
+            TextMode::Markdown => html_to_markdown(&string),
+            TextMode::Plain => html_to_text(&string),
+        };
+        if !text.is_empty() {
             self.0
-                .entries
+                .legacy_texts
                 .insert(name.to_string(), remove_html(&string));
+            self.insert(name, EntryValue::Text(text));
         }
         self
     }
@@ -98,11 +314,30 @@ impl GenModuleBuilder {
         self
     }
 
+    pub fn url(&mut self, name: &str, url: String) -> &mut Self {
+        if !url.is_empty() {
+            self.insert(name, EntryValue::Url(url));
+        }
+        self
+    }
+
     pub fn bool(&mut self, name: &str, bool: bool) -> &mut Self {
-        if bool {
-            self.0.entries.insert(name.to_string(), "Ja".to_string());
-        } else {
-            self.0.entries.insert(name.to_string(), "Nein".to_string());
+        self.insert(name, EntryValue::Bool(bool))
+    }
+
+    /// a text that is translated when it is shown
+    pub fn label(&mut self, name: &str, key: &str) -> &mut Self {
+        self.insert(name, EntryValue::Label(key.to_string()))
+    }
+
+    pub fn insert(&mut self, name: &str, value: EntryValue) -> &mut Self {
+        self.0.entries.insert(name.to_string(), value);
+        self
+    }
+
+    pub fn insert_option(&mut self, name: &str, value: Option<EntryValue>) -> &mut Self {
+        if let Some(value) = value {
+            self.insert(name, value);
         }
         self
     }
@@ -130,18 +365,19 @@ impl GenModuleBuilder {
             let file_url = remove_revision(&content.fileurl);
             self.0.files.insert(content.filename, file_url);
         } else {
-            self.0.entries.insert(content.filename, content.fileurl);
+            self.0
+                .entries
+                .insert(content.filename, EntryValue::Url(content.fileurl));
         }
         self
     }
 }
 
-// descriptions often contain html tags for presentation on the moodle website, we want plaintext
+// how descriptions were stored before they were converted to markdown, see GenModule::migrate_texts
 fn remove_html(string: &str) -> String {
-    let mut chars = string.chars();
     let mut open_brackets = 0;
     let mut new_str = String::new();
-    while let Some(char) = chars.next() {
+    for char in string.chars() {
         if char == '<' {
             open_brackets += 1;
             new_str.push(' ');
@@ -0,0 +1,17 @@
+/// Icons that come with this crate. Modules refer to them as `attachment://<file name>`, so they
+/// have to be uploaded together with the message that shows them, see `bundled`
+pub const SECTION_ICON_URL: &str = "attachment://section.png";
+
+const SECTION_ICON: &[u8] = include_bytes!("../../assets/section.png");
+
+/// the discord attachment that sections used before the icon was bundled
+pub(crate) const LEGACY_SECTION_ICON_URL: &str =
+    "https://cdn.discordapp.com/attachments/1092233307867070554/1095647451739865108/section.png";
+
+/// file name and content of a bundled icon, None for icons on the moodle site
+pub fn bundled(url: &str) -> Option<(&'static str, &'static [u8])> {
+    match url {
+        SECTION_ICON_URL => Some(("section.png", SECTION_ICON)),
+        _ => None,
+    }
+}
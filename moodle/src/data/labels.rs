@@ -0,0 +1,241 @@
+use serde::{Deserialize, Serialize};
+
+/// Language of the labels of generated entries
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    En,
+    #[default]
+    De,
+}
+
+impl Locale {
+    /// e.g. "en", "de"
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::De => "de",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Locale> {
+        match code.to_ascii_lowercase().as_str() {
+            "en" => Some(Locale::En),
+            "de" => Some(Locale::De),
+            _ => None,
+        }
+    }
+
+    pub fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => EN,
+            Locale::De => DE,
+        }
+    }
+
+    /// translate a machine key. Keys with an argument ("chapter:Einleitung") keep the argument,
+    /// unknown keys like the date labels moodle sends are shown as they are
+    pub fn label(self, key: &str) -> String {
+        if let Some(label) = self.lookup(key) {
+            return label.to_string();
+        }
+        if let Some((key, argument)) = key.split_once(':') {
+            if PARAMETERIZED.contains(&key) {
+                if let Some(label) = self.lookup(key) {
+                    return format!("{label}: {argument}");
+                }
+            }
+        }
+        key.to_string()
+    }
+
+    fn lookup(self, key: &str) -> Option<&'static str> {
+        self.catalog()
+            .iter()
+            .find(|(catalog_key, _)| *catalog_key == key)
+            .map(|(_, label)| *label)
+    }
+}
+
+/// keys that are followed by a name, e.g. the title of a book chapter
+const PARAMETERIZED: &[&str] = &["chapter", "chapter_text", "page"];
+
+const EN: &[(&str, &str)] = &[
+    ("allow_submissions_from", "Allow submissions from"),
+    ("allow_updates", "Changeable"),
+    ("assessment_end", "Assessment end"),
+    ("assessment_start", "Assessment start"),
+    ("attempts", "Attempts"),
+    ("chapter", "Chapter"),
+    ("chapter_text", "Chapter text"),
+    ("chat_time", "Start time"),
+    ("content", "Content"),
+    ("cutoff_date", "Cut-off date"),
+    ("description", "Description"),
+    ("discussions", "Number of discussions"),
+    ("due_date", "Due date"),
+    ("entries", "Entries"),
+    ("extension_due_date", "Extended until"),
+    ("first_page", "Start page"),
+    ("format", "Format"),
+    ("grading_due_date", "Grading due date"),
+    ("instruct_authors", "Instructions for submission"),
+    ("instruct_reviewers", "Instructions for assessment"),
+    ("link", "Link"),
+    ("multiple_answers", "Multiple choice"),
+    ("no", "No"),
+    ("page", "Page"),
+    ("phase", "Phase"),
+    ("questions", "Number of questions"),
+    ("rating", "Rating"),
+    ("retake", "Retakes allowed"),
+    ("revision", "Revision"),
+    ("submission_draft", "Draft (not submitted)"),
+    ("submission_end", "Submissions deadline"),
+    ("submission_new", "No submission"),
+    ("submission_reopened", "Reopened"),
+    ("submission_start", "Submissions start"),
+    ("submission_status", "Submission status"),
+    ("submission_submitted", "Submitted"),
+    ("summary", "Summary"),
+    ("time_available_from", "Entries from"),
+    ("time_available_to", "Entries until"),
+    ("time_close", "Close date"),
+    ("time_limit", "Time limit"),
+    ("time_modified", "Last modified"),
+    ("time_open", "Open date"),
+    ("time_view_from", "Read only from"),
+    ("time_view_to", "Read only until"),
+    ("type", "Type"),
+    ("workshop_phase_assessment", "Assessment"),
+    ("workshop_phase_closed", "Closed"),
+    ("workshop_phase_evaluation", "Grading evaluation"),
+    ("workshop_phase_setup", "Setup"),
+    ("workshop_phase_submission", "Submission"),
+    ("workshop_phase_unknown", "Unknown"),
+    ("yes", "Yes"),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("allow_submissions_from", "Abgabebeginn"),
+    ("allow_updates", "Änderbar"),
+    ("assessment_end", "Beurteilungsende"),
+    ("assessment_start", "Beurteilungsbeginn"),
+    ("attempts", "Versuche"),
+    ("chapter", "Kapitel"),
+    ("chapter_text", "Kapiteltext"),
+    ("chat_time", "Startzeit"),
+    ("content", "Inhalt"),
+    ("cutoff_date", "Letzte Abgabemöglichkeit"),
+    ("description", "Beschreibung"),
+    ("discussions", "Anzahl an Diskussionen"),
+    ("due_date", "Abgabedatum"),
+    ("entries", "Einträge"),
+    ("extension_due_date", "Verlängert bis"),
+    ("first_page", "Startseite"),
+    ("format", "Format"),
+    ("grading_due_date", "Bewertungstermin"),
+    ("instruct_authors", "Anleitung zur Einreichung"),
+    ("instruct_reviewers", "Anleitung zur Beurteilung"),
+    ("link", "Link"),
+    ("multiple_answers", "Mehrfachauswahl"),
+    ("no", "Nein"),
+    ("page", "Seite"),
+    ("phase", "Phase"),
+    ("questions", "Fragenanzahl"),
+    ("rating", "Bewertung"),
+    ("retake", "Wiederholbar"),
+    ("revision", "Revision"),
+    ("submission_draft", "Entwurf (nicht abgegeben)"),
+    ("submission_end", "Einreichungsende"),
+    ("submission_new", "Keine Abgabe"),
+    ("submission_reopened", "Erneut geöffnet"),
+    ("submission_start", "Einreichungsbeginn"),
+    ("submission_status", "Abgabestatus"),
+    ("submission_submitted", "Abgegeben"),
+    ("summary", "Zusammenfassung"),
+    ("time_available_from", "Eintragen ab"),
+    ("time_available_to", "Eintragen bis"),
+    ("time_close", "Schlussdatum"),
+    ("time_limit", "Zeitlimit"),
+    ("time_modified", "Änderungsdatum"),
+    ("time_open", "Öffnungsdatum"),
+    ("time_view_from", "Ansehen ab"),
+    ("time_view_to", "Ansehen bis"),
+    ("type", "Typ"),
+    ("workshop_phase_assessment", "Beurteilung"),
+    ("workshop_phase_closed", "Geschlossen"),
+    ("workshop_phase_evaluation", "Bewertung der Beurteilungen"),
+    ("workshop_phase_setup", "Vorbereitung"),
+    ("workshop_phase_submission", "Einreichung"),
+    ("workshop_phase_unknown", "Unbekannt"),
+    ("yes", "Ja"),
+];
+
+/// German keys of snapshots from before the machine keys, misspellings included
+const LEGACY: &[(&str, &str)] = &[
+    ("Abgabebeginn", "allow_submissions_from"),
+    ("Abgabedatum", "due_date"),
+    ("Abgabestatus", "submission_status"),
+    ("Ansehen ab", "time_view_from"),
+    ("Ansehen bis", "time_view_to"),
+    ("Anleitung zur Beurteilung", "instruct_reviewers"),
+    ("Anleitung zur Einreichung", "instruct_authors"),
+    ("Anzahl an Disskusionen", "discussions"),
+    ("Bescheibung", "description"),
+    ("Beschreibung", "description"),
+    ("Beurteilungsbeginn", "assessment_start"),
+    ("Beurteilungsende", "assessment_end"),
+    ("Bewertung", "rating"),
+    ("Bewertungstermin", "grading_due_date"),
+    ("Content", "content"),
+    ("Einreichungsbeginn", "submission_start"),
+    ("Einreichungsende", "submission_end"),
+    ("Einträge", "entries"),
+    ("Eintragen ab", "time_available_from"),
+    ("Eintragen bis", "time_available_to"),
+    ("Format", "format"),
+    ("Fragenanzahl", "questions"),
+    ("Letzte Abgabemöglichkeit", "cutoff_date"),
+    ("Link", "link"),
+    ("Mehrfachauswahl", "multiple_answers"),
+    ("Phase", "phase"),
+    ("Revision", "revision"),
+    ("Schlussdatum", "time_close"),
+    ("Startseite", "first_page"),
+    ("Startzeit", "chat_time"),
+    ("Type", "type"),
+    ("Verlängert bis", "extension_due_date"),
+    ("Versuche", "attempts"),
+    ("Wiederholbar", "retake"),
+    ("Zeitlimit", "time_limit"),
+    ("Zusammenfassung", "summary"),
+    ("Änderbar", "allow_updates"),
+    ("Änderungsdatum", "time_modified"),
+    ("Öffnungsdatum", "time_open"),
+];
+
+/// machine key of an entry key that was stored in German, None if it is no legacy key
+pub fn legacy_key(key: &str) -> Option<String> {
+    if let Some((_, machine)) = LEGACY.iter().find(|(german, _)| *german == key) {
+        return Some(machine.to_string());
+    }
+    let (prefix, argument) = key.split_once(": ")?;
+    match prefix {
+        "Kapitel" => Some(format!("chapter:{argument}")),
+        "Seite" => Some(format!("page:{argument}")),
+        _ => None,
+    }
+}
+
+/// label key of a value that was stored as German text, e.g. the phase of a workshop
+pub fn legacy_value(key: &str, text: &str) -> Option<String> {
+    let prefix = match key {
+        "phase" => "workshop_phase_",
+        "submission_status" => "submission_",
+        _ => return None,
+    };
+    DE.iter()
+        .find(|(label_key, label)| label_key.starts_with(prefix) && *label == text)
+        .map(|(label_key, _)| label_key.to_string())
+}
@@ -0,0 +1,72 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+/// fields of an answer that the types dont know about, e.g. from a newer moodle version
+pub type Extra = BTreeMap<String, Value>;
+
+/// An element of a list that couldnt be parsed and was left out of the result
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseWarning {
+    /// the web service function that sent the list
+    pub function: String,
+    /// rust type of the element
+    pub item: &'static str,
+    /// position of the element inside its list
+    pub index: usize,
+    pub message: String,
+}
+
+/// (item, index, message) of every skipped element while an answer is decoded
+type Skipped = Vec<(&'static str, usize, String)>;
+
+thread_local! {
+    static SKIPPED: RefCell<Option<Skipped>> = const { RefCell::new(None) };
+}
+
+/// A plain json array that skips elements it cant parse instead of failing
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent, bound(deserialize = "T: DeserializeOwned"))]
+pub struct LenientVec<T>(#[serde(deserialize_with = "skip_invalid")] pub Vec<T>);
+
+/// use with `deserialize_with`, a missing or null list is empty and broken elements are skipped
+pub fn skip_invalid<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let values = Option::<Vec<Value>>::deserialize(deserializer)?.unwrap_or_default();
+    let mut items = Vec::with_capacity(values.len());
+    for (index, value) in values.into_iter().enumerate() {
+        match serde_json::from_value(value) {
+            Ok(item) => items.push(item),
+            Err(why) => SKIPPED.with(|skipped| {
+                if let Some(skipped) = skipped.borrow_mut().as_mut() {
+                    skipped.push((std::any::type_name::<T>(), index, why.to_string()));
+                }
+            }),
+        }
+    }
+    Ok(items)
+}
+
+/// run `decode` and return the elements it skipped. Decoding is synchronous, so the skipped
+/// elements of other requests on the same thread cant end up in here
+pub(crate) fn collect<T>(function: &str, decode: impl FnOnce() -> T) -> (T, Vec<ParseWarning>) {
+    let outer = SKIPPED.with(|skipped| skipped.replace(Some(vec![])));
+    let res = decode();
+    let skipped = SKIPPED.with(|skipped| skipped.replace(outer));
+    let warnings = skipped
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(item, index, message)| ParseWarning {
+            function: function.to_string(),
+            item,
+            index,
+            message,
+        })
+        .collect();
+    (res, warnings)
+}
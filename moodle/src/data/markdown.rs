@@ -0,0 +1,513 @@
+/// Convert the html of descriptions and forum posts to the markdown discord understands.
+/// Formatting without a markdown equivalent is dropped, the text is kept
+pub fn html_to_markdown(html: &str) -> String {
+    convert(html, Markdown::default())
+}
+
+/// Like html_to_markdown but without any markdown, links are followed by their url in brackets
+pub fn html_to_text(html: &str) -> String {
+    let plain = Markdown {
+        plain: true,
+        ..Markdown::default()
+    };
+    convert(html, plain)
+}
+
+fn convert(html: &str, mut markdown: Markdown) -> String {
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        markdown.text(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        match rest.find('>') {
+            Some(end) => {
+                markdown.tag(&rest[1..end]);
+                rest = &rest[end + 1..];
+            }
+            // not a tag, just a lonely "<"
+            None => {
+                markdown.text(rest);
+                rest = "";
+            }
+        }
+    }
+    markdown.text(rest);
+    markdown.finish()
+}
+
+/// An element whose markdown depends on its content, which is only known once it is closed
+enum Open {
+    Inline(&'static str),
+    Link(Option<String>),
+    Heading,
+    Quote,
+    Pre,
+}
+
+#[derive(Default)]
+struct Markdown {
+    out: String,
+    /// start of the element in `out` and what it turns into
+    open: Vec<(usize, &'static str, Open)>,
+    /// None for unordered lists, the next number for ordered lists
+    lists: Vec<Option<u32>>,
+    /// inside <script> or <style>
+    hidden: usize,
+    pre: usize,
+    /// plain text without markdown
+    plain: bool,
+}
+
+impl Markdown {
+    fn text(&mut self, text: &str) {
+        if self.hidden > 0 || text.is_empty() {
+            return;
+        }
+        let text = decode_entities(text);
+        if self.pre > 0 {
+            self.out.push_str(&text);
+            return;
+        }
+        for char in text.chars() {
+            if char.is_whitespace() {
+                if !self.out.is_empty() && !self.out.ends_with([' ', '\n']) {
+                    self.out.push(' ');
+                }
+            } else {
+                let special = matches!(char, '*' | '_' | '~' | '`' | '|' | '\\');
+                if special && !self.plain && !self.in_url() {
+                    self.out.push('\\');
+                }
+                self.out.push(char);
+            }
+        }
+    }
+
+    /// links in plain text are shown as they are, escaping would break them
+    fn in_url(&self) -> bool {
+        let word = self
+            .out
+            .rsplit([' ', '\n', '(', '['])
+            .next()
+            .unwrap_or_default();
+        word.starts_with("http://") || word.starts_with("https://")
+    }
+
+    fn tag(&mut self, tag: &str) {
+        let (closing, tag) = match tag.strip_prefix('/') {
+            Some(tag) => (true, tag),
+            None => (false, tag),
+        };
+        let name_end = tag
+            .find(|c: char| c.is_whitespace() || c == '/')
+            .unwrap_or(tag.len());
+        let name = tag[..name_end].to_ascii_lowercase();
+        let name = name.as_str();
+
+        match (name, closing) {
+            ("script" | "style", false) => self.hidden += 1,
+            ("script" | "style", true) => self.hidden = self.hidden.saturating_sub(1),
+            _ if self.hidden > 0 => {}
+            ("br", _) => self.out.push('\n'),
+            ("p" | "div" | "section" | "article" | "table" | "hr", _) => self.block(),
+            ("tr", _) => self.line(),
+            ("td" | "th", false) if !self.out.ends_with('\n') && !self.out.is_empty() => {
+                self.out.push_str(" | ")
+            }
+            ("strong" | "b", _) => self.inline(closing, "b", "**"),
+            ("em" | "i", _) => self.inline(closing, "i", "*"),
+            ("u", _) => self.inline(closing, "u", "__"),
+            ("s" | "strike" | "del", _) => self.inline(closing, "s", "~~"),
+            ("code", _) if self.pre == 0 => self.inline(closing, "code", "`"),
+            ("a", false) => self.open("a", Open::Link(attribute(tag, "href"))),
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", false) => self.heading(),
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", true) => self.close("h"),
+            ("blockquote", false) => {
+                self.block();
+                self.open("blockquote", Open::Quote);
+            }
+            ("pre", false) => {
+                self.block();
+                self.pre += 1;
+                self.open("pre", Open::Pre);
+            }
+            ("ul", false) => {
+                self.line();
+                self.lists.push(None);
+            }
+            ("ol", false) => {
+                self.line();
+                self.lists.push(Some(1));
+            }
+            ("ul" | "ol", true) => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.block();
+                } else {
+                    self.line();
+                }
+            }
+            ("li", false) => self.list_item(),
+            ("li", true) => self.line(),
+            (_, true) => self.close(name),
+            _ => {}
+        }
+    }
+
+    fn open(&mut self, name: &'static str, open: Open) {
+        self.open.push((self.out.len(), name, open));
+    }
+
+    fn inline(&mut self, closing: bool, name: &'static str, marker: &'static str) {
+        if closing {
+            self.close(name);
+        } else {
+            self.open(name, Open::Inline(marker));
+        }
+    }
+
+    /// discord only shows headings in messages, bold text works everywhere
+    fn heading(&mut self) {
+        self.block();
+        self.open("h", Open::Heading);
+    }
+
+    fn list_item(&mut self) {
+        self.line();
+        let depth = self.lists.len().max(1);
+        let indent = "  ".repeat(depth - 1);
+        let marker = match self.lists.last_mut() {
+            Some(Some(number)) => {
+                *number += 1;
+                format!("{}. ", *number - 1)
+            }
+            _ => "- ".to_string(),
+        };
+        self.out.push_str(&indent);
+        self.out.push_str(&marker);
+    }
+
+    /// finish the innermost element called `name`, elements that were left open inside it are dropped
+    fn close(&mut self, name: &str) {
+        let Some(index) = self.open.iter().rposition(|(_, open, _)| *open == name) else {
+            return;
+        };
+        let Some((start, _, open)) = self.open.split_off(index).into_iter().next() else {
+            return;
+        };
+        let content = self.out.split_off(start);
+
+        match open {
+            Open::Inline(marker) => {
+                let trimmed = content.trim();
+                if trimmed.is_empty() || self.plain {
+                    self.out.push_str(&content);
+                    return;
+                }
+                // "** bold **" isnt bold, the spaces have to go outside
+                if content.starts_with(char::is_whitespace) && !self.out.ends_with([' ', '\n']) {
+                    self.out.push(' ');
+                }
+                self.out.push_str(marker);
+                self.out.push_str(trimmed);
+                self.out.push_str(marker);
+                if content.ends_with(char::is_whitespace) {
+                    self.out.push(' ');
+                }
+            }
+            Open::Link(href) => {
+                let text = content.trim();
+                match href {
+                    Some(href) if href.starts_with("http") || href.starts_with("mailto:") => {
+                        if text.is_empty() || text == href {
+                            self.out.push_str(&href);
+                        } else if self.plain {
+                            self.out.push_str(&format!("{text} ({href})"));
+                        } else {
+                            self.out.push_str(&format!("[{text}]({href})"));
+                        }
+                    }
+                    _ => self.out.push_str(&content),
+                }
+            }
+            Open::Heading => {
+                let text = content.trim();
+                if self.plain {
+                    self.out.push_str(text);
+                } else if !text.is_empty() {
+                    self.out.push_str(&format!("**{text}**"));
+                }
+                self.block();
+            }
+            Open::Quote => {
+                let prefix = if self.plain { "" } else { "> " };
+                for line in content.trim().lines() {
+                    self.out.push_str(prefix);
+                    self.out.push_str(line);
+                    self.out.push('\n');
+                }
+                self.block();
+            }
+            Open::Pre => {
+                self.pre = self.pre.saturating_sub(1);
+                if self.plain {
+                    self.out.push_str(content.trim_matches('\n'));
+                } else {
+                    self.out.push_str("```\n");
+                    self.out.push_str(content.trim_matches('\n'));
+                    self.out.push_str("\n```");
+                }
+                self.block();
+            }
+        }
+    }
+
+    /// start a new line
+    fn line(&mut self) {
+        // the start of an open element must stay where it is
+        let keep = self.open.iter().map(|(start, ..)| *start).max();
+        let trimmed = self
+            .out
+            .trim_end_matches(' ')
+            .len()
+            .max(keep.unwrap_or_default());
+        self.out.truncate(trimmed);
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    /// start a new paragraph
+    fn block(&mut self) {
+        self.line();
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn finish(self) -> String {
+        let mut result = String::new();
+        let mut empty_lines = 0;
+        for line in self.out.trim().lines() {
+            let line = line.trim_end();
+            if line.is_empty() {
+                empty_lines += 1;
+                if empty_lines > 1 {
+                    continue;
+                }
+            } else {
+                empty_lines = 0;
+            }
+            result.push_str(line);
+            result.push('\n');
+        }
+        result.trim_end().to_string()
+    }
+}
+
+/// the value of an attribute like href="..." inside a tag
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let mut search = 0;
+    while let Some(found) = lower[search..].find(name) {
+        let start = search + found;
+        search = start + name.len();
+        // only whole attribute names, "data-href" isnt "href"
+        if !lower[..start].ends_with(char::is_whitespace) {
+            continue;
+        }
+        let value = tag[search..].trim_start().strip_prefix('=')?.trim_start();
+        let value = match value.chars().next()? {
+            quote @ ('"' | '\'') => value[1..].split(quote).next()?,
+            _ => value.split(char::is_whitespace).next()?,
+        };
+        return Some(decode_entities(value));
+    }
+    None
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| Some((entity(&rest[1..end])?, end)));
+        match decoded {
+            Some((char, end)) => {
+                result.push(char);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// named entities of the latin-1 supplement, starting at U+00A0
+const LATIN_1: [&str; 96] = [
+    "nbsp", "iexcl", "cent", "pound", "curren", "yen", "brvbar", "sect", "uml", "copy", "ordf",
+    "laquo", "not", "shy", "reg", "macr", "deg", "plusmn", "sup2", "sup3", "acute", "micro",
+    "para", "middot", "cedil", "sup1", "ordm", "raquo", "frac14", "frac12", "frac34", "iquest",
+    "Agrave", "Aacute", "Acirc", "Atilde", "Auml", "Aring", "AElig", "Ccedil", "Egrave", "Eacute",
+    "Ecirc", "Euml", "Igrave", "Iacute", "Icirc", "Iuml", "ETH", "Ntilde", "Ograve", "Oacute",
+    "Ocirc", "Otilde", "Ouml", "times", "Oslash", "Ugrave", "Uacute", "Ucirc", "Uuml", "Yacute",
+    "THORN", "szlig", "agrave", "aacute", "acirc", "atilde", "auml", "aring", "aelig", "ccedil",
+    "egrave", "eacute", "ecirc", "euml", "igrave", "iacute", "icirc", "iuml", "eth", "ntilde",
+    "ograve", "oacute", "ocirc", "otilde", "ouml", "divide", "oslash", "ugrave", "uacute", "ucirc",
+    "uuml", "yacute", "thorn", "yuml",
+];
+
+/// greek letters, starting at U+0391. U+03A2 is unassigned, the final sigma only exists in lowercase
+const GREEK: [&str; 25] = [
+    "Alpha", "Beta", "Gamma", "Delta", "Epsilon", "Zeta", "Eta", "Theta", "Iota", "Kappa",
+    "Lambda", "Mu", "Nu", "Xi", "Omicron", "Pi", "Rho", "", "Sigma", "Tau", "Upsilon", "Phi",
+    "Chi", "Psi", "Omega",
+];
+
+fn entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    // the browser shows a non breaking space, discord would keep it from wrapping
+    if name == "nbsp" {
+        return Some(' ');
+    }
+    if let Some(index) = LATIN_1.iter().position(|entity| *entity == name) {
+        return char::from_u32(0xA0 + index as u32);
+    }
+    if let Some(index) = GREEK.iter().position(|entity| *entity == name) {
+        return char::from_u32(0x391 + index as u32);
+    }
+    let lowercase_greek = GREEK
+        .iter()
+        .position(|entity| !entity.is_empty() && entity.to_lowercase() == name);
+    if let Some(index) = lowercase_greek {
+        return char::from_u32(0x3B1 + index as u32);
+    }
+    let code = match name {
+        "quot" => 0x22,
+        "amp" => 0x26,
+        "apos" => 0x27,
+        "lt" => 0x3C,
+        "gt" => 0x3E,
+        "OElig" => 0x152,
+        "oelig" => 0x153,
+        "Scaron" => 0x160,
+        "scaron" => 0x161,
+        "Yuml" => 0x178,
+        "fnof" => 0x192,
+        "circ" => 0x2C6,
+        "tilde" => 0x2DC,
+        "sigmaf" => 0x3C2,
+        "thetasym" => 0x3D1,
+        "upsih" => 0x3D2,
+        "piv" => 0x3D6,
+        "ensp" => 0x2002,
+        "emsp" => 0x2003,
+        "thinsp" => 0x2009,
+        "zwnj" => 0x200C,
+        "zwj" => 0x200D,
+        "lrm" => 0x200E,
+        "rlm" => 0x200F,
+        "ndash" => 0x2013,
+        "mdash" => 0x2014,
+        "lsquo" => 0x2018,
+        "rsquo" => 0x2019,
+        "sbquo" => 0x201A,
+        "ldquo" => 0x201C,
+        "rdquo" => 0x201D,
+        "bdquo" => 0x201E,
+        "dagger" => 0x2020,
+        "Dagger" => 0x2021,
+        "bull" => 0x2022,
+        "hellip" => 0x2026,
+        "permil" => 0x2030,
+        "prime" => 0x2032,
+        "Prime" => 0x2033,
+        "lsaquo" => 0x2039,
+        "rsaquo" => 0x203A,
+        "oline" => 0x203E,
+        "frasl" => 0x2044,
+        "euro" => 0x20AC,
+        "trade" => 0x2122,
+        "larr" => 0x2190,
+        "uarr" => 0x2191,
+        "rarr" => 0x2192,
+        "darr" => 0x2193,
+        "harr" => 0x2194,
+        "lArr" => 0x21D0,
+        "uArr" => 0x21D1,
+        "rArr" => 0x21D2,
+        "dArr" => 0x21D3,
+        "hArr" => 0x21D4,
+        "forall" => 0x2200,
+        "part" => 0x2202,
+        "exist" => 0x2203,
+        "empty" => 0x2205,
+        "nabla" => 0x2207,
+        "isin" => 0x2208,
+        "notin" => 0x2209,
+        "ni" => 0x220B,
+        "prod" => 0x220F,
+        "sum" => 0x2211,
+        "minus" => 0x2212,
+        "lowast" => 0x2217,
+        "radic" => 0x221A,
+        "prop" => 0x221D,
+        "infin" => 0x221E,
+        "ang" => 0x2220,
+        "and" => 0x2227,
+        "or" => 0x2228,
+        "cap" => 0x2229,
+        "cup" => 0x222A,
+        "int" => 0x222B,
+        "there4" => 0x2234,
+        "sim" => 0x223C,
+        "cong" => 0x2245,
+        "asymp" => 0x2248,
+        "ne" => 0x2260,
+        "equiv" => 0x2261,
+        "le" => 0x2264,
+        "ge" => 0x2265,
+        "sub" => 0x2282,
+        "sup" => 0x2283,
+        "nsub" => 0x2284,
+        "sube" => 0x2286,
+        "supe" => 0x2287,
+        "oplus" => 0x2295,
+        "otimes" => 0x2297,
+        "perp" => 0x22A5,
+        "sdot" => 0x22C5,
+        "lceil" => 0x2308,
+        "rceil" => 0x2309,
+        "lfloor" => 0x230A,
+        "rfloor" => 0x230B,
+        "lang" => 0x2329,
+        "rang" => 0x232A,
+        "loz" => 0x25CA,
+        "spades" => 0x2660,
+        "clubs" => 0x2663,
+        "hearts" => 0x2665,
+        "diams" => 0x2666,
+        _ => return None,
+    };
+    char::from_u32(code)
+}
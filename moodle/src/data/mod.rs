@@ -2,5 +2,11 @@ pub mod comparable;
 pub mod course_contents;
 pub mod course_traversal;
 pub mod gen_module;
+pub mod icons;
+pub mod labels;
+pub mod lenient;
+pub mod markdown;
 pub(crate) mod modules;
+pub mod multilang;
 pub mod other_content;
+pub mod registry;
@@ -1,33 +1,39 @@
-use crate::data::course_contents::{Generate, Id};
-use crate::data::gen_module::GenModuleBuilder;
+use crate::data::course_contents::{request_details, DetailError, Generate, Id, ModuleKey};
+use crate::data::gen_module::{EntryValue, GenModuleBuilder};
+use crate::data::lenient::{skip_invalid, Extra};
 use crate::data::modules::unknown::CourseModule;
 use crate::data::other_content::file::FileInfo;
-use crate::Moodle;
+use crate::data::other_content::submission_status::SubmissionStatus;
+use crate::{Moodle, MoodleError};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct Root {
+    #[serde(deserialize_with = "skip_invalid")]
     pub courses: Vec<Course>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct Course {
     //pub id: i64,
     //pub fullname: String,
     //pub shortname: String,
     //pub timemodified: i64,
+    #[serde(deserialize_with = "skip_invalid")]
     pub assignments: Vec<Assignment>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct Assignment {
-    //pub id: i64,
+    pub id: i64,
     pub cmid: i64,
-    //pub course: i64,
+    pub course: i64,
     pub name: String,
     //pub nosubmissions: i64,
     //pub submissiondrafts: i64,
@@ -57,11 +63,19 @@ pub struct Assignment {
     pub intro: String,
     //pub introformat: i64,
     //pub introfiles: Vec<Value>,
+    #[serde(deserialize_with = "skip_invalid")]
     pub introattachments: Vec<FileInfo>,
+    #[serde(skip)]
+    pub submission_status: Option<SubmissionStatus>, // of the token's user, requested separately
+    #[serde(skip)]
+    pub failure: Option<DetailError>, // the submission status couldnt be requested
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
+#[allow(dead_code)]
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct Config {
     pub plugin: String,
     pub subtype: String,
@@ -69,26 +83,79 @@ pub struct Config {
     pub value: String,
 }
 
+impl SubmissionStatus {
+    /// known statuses are translated when they are shown
+    fn describe(&self) -> EntryValue {
+        let label = match self.status.as_deref() {
+            Some("submitted") => "submission_submitted",
+            Some("draft") => "submission_draft",
+            Some("reopened") => "submission_reopened",
+            Some("new") | None => "submission_new",
+            Some(status) => return EntryValue::Text(status.to_string()),
+        };
+        EntryValue::Label(label.to_string())
+    }
+
+    fn rating(&self) -> Option<String> {
+        match (&self.grade, &self.feedback_comments) {
+            (Some(grade), Some(comments)) => Some(format!("{grade}\n{comments}")),
+            (Some(grade), None) => Some(grade.clone()),
+            (None, Some(comments)) => Some(comments.clone()),
+            (None, None) => None,
+        }
+    }
+}
+
 impl Id for Assignment {
-    fn get_id(&self) -> i64 {
-        self.cmid
+    fn get_id(&self) -> ModuleKey {
+        ModuleKey::Module(self.cmid)
     }
 }
 
 #[async_trait]
 impl Generate for Assignment {
-    async fn request(client: &Moodle, id: i64) -> anyhow::Result<Vec<Self>> {
-        client.get_assignments_for_course(id).await
+    const FUNCTION: &'static str = "mod_assign_get_assignments";
+
+    async fn request_many(
+        client: &Moodle,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Self>>, MoodleError> {
+        let mut assignments = client.get_assignments_for_courses(course_ids).await?;
+        let ids: Vec<i64> = assignments.values().flatten().map(|a| a.id).collect();
+        let mut statuses = request_details(client, "mod_assign_get_submission_status", ids, |id| {
+            client.get_submission_status(id)
+        })
+        .await;
+
+        for assignment in assignments.values_mut().flatten() {
+            match statuses.remove(&assignment.id) {
+                Some(Ok(status)) => assignment.submission_status = Some(status),
+                Some(Err(why)) => assignment.failure = Some(why),
+                None => {}
+            }
+        }
+        Ok(assignments)
+    }
+
+    fn failure(&self) -> Option<Arc<MoodleError>> {
+        self.failure.as_ref().map(|why| why.0.clone())
     }
 
     fn gen(self, builder: &mut GenModuleBuilder, _: CourseModule) -> &mut GenModuleBuilder {
+        let status = self.submission_status.as_ref();
         builder
-            .string("Bescheibung", self.intro)
-            .date("Abgabebeginn", self.allowsubmissionsfromdate)
-            .date("Abgabedatum", self.duedate)
-            .date("Letzte Abgabemöglichkeit", self.cutoffdate)
-            .date("Bewertungstermin", self.gradingduedate)
-            .date("Änderungsdatum", self.timemodified)
+            .string("description", self.intro)
+            .date("allow_submissions_from", self.allowsubmissionsfromdate)
+            .date("due_date", self.duedate)
+            .date("cutoff_date", self.cutoffdate)
+            .date("grading_due_date", self.gradingduedate)
+            .date("time_modified", self.timemodified)
+            .insert_option("submission_status", status.map(SubmissionStatus::describe))
+            .date_option(
+                "extension_due_date",
+                status.and_then(|s| s.extension_due_date),
+            )
+            .string_option("rating", status.and_then(SubmissionStatus::rating))
             .files(self.introattachments)
     }
 }
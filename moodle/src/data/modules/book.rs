@@ -0,0 +1,192 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::data::course_contents::{request_details, DetailError, Generate, Id, ModuleKey};
+use crate::data::gen_module::{EntryValue, GenModuleBuilder};
+use crate::data::lenient::{skip_invalid, Extra};
+use crate::data::modules::unknown::{Content, CourseModule};
+use crate::{Moodle, MoodleError};
+
+/// larger chapter pages arent downloaded
+const MAX_CHAPTER_SIZE: u64 = 1 << 20;
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Root {
+    #[serde(deserialize_with = "skip_invalid")]
+    pub books: Vec<Book>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Book {
+    //pub id: i64,
+    pub coursemodule: i64,
+    pub course: i64,
+    pub name: String,
+    pub intro: String,
+    //pub introformat: i64,
+    //pub introfiles: Vec<Value>,
+    //pub numbering: i64,
+    //pub navstyle: i64,
+    //pub customtitles: i64,
+    //pub revision: i64,
+    //pub timecreated: i64,
+    pub timemodified: i64,
+    //pub section: i64,
+    //pub visible: i64,
+    //pub groupmode: i64,
+    //pub groupingid: i64,
+    #[serde(skip)]
+    pub chapter_texts: HashMap<i64, String>, // chapter id -> html of the chapter, downloaded separately
+    #[serde(skip)]
+    pub failure: Option<DetailError>, // a chapter couldnt be downloaded
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// an entry of the table of contents, stored as json in the "structure" content of the course module
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Chapter {
+    title: String,
+    href: String, // "<chapter id>/index.html"
+    #[serde(default)]
+    subitems: Vec<Chapter>,
+}
+
+impl Id for Book {
+    fn get_id(&self) -> ModuleKey {
+        ModuleKey::Module(self.coursemodule)
+    }
+}
+
+#[async_trait]
+impl Generate for Book {
+    const FUNCTION: &'static str = "mod_book_get_books_by_courses";
+
+    async fn request_many(
+        client: &Moodle,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Self>>, MoodleError> {
+        client.get_books_for_courses(course_ids).await
+    }
+
+    /// the text of a chapter is only available as the index.html file of the course contents
+    async fn add_details(
+        books: &mut HashMap<i64, Vec<Self>>,
+        course_modules: &HashMap<i64, Vec<CourseModule>>,
+        client: &Moodle,
+    ) {
+        let pages: HashMap<i64, (i64, &str)> = course_modules
+            .values()
+            .flatten()
+            .flat_map(|module| {
+                module.contents.iter().filter_map(|content| {
+                    Some((chapter_id(content)?, (module.id, content.fileurl.as_str())))
+                })
+            })
+            .collect();
+        let ids = pages.keys().copied().collect();
+        let texts = request_details(client, Self::FUNCTION, ids, |id| {
+            let link = pages[&id].1.to_string();
+            async move {
+                match client.download_file(link, MAX_CHAPTER_SIZE).await {
+                    Ok(bytes) => Ok(Some(String::from_utf8_lossy(&bytes).into_owned())),
+                    // a huge chapter is only compared by its time
+                    Err(MoodleError::TooLarge { .. }) => Ok(None),
+                    Err(why) => Err(why),
+                }
+            }
+        })
+        .await;
+
+        for (id, text) in texts {
+            let book = books
+                .values_mut()
+                .flatten()
+                .find(|book| book.coursemodule == pages[&id].0);
+            let Some(book) = book else {
+                continue;
+            };
+            match text {
+                Ok(Some(text)) => {
+                    book.chapter_texts.insert(id, text);
+                }
+                Ok(None) => {}
+                Err(why) => book.failure = Some(why),
+            }
+        }
+    }
+
+    fn failure(&self) -> Option<Arc<MoodleError>> {
+        self.failure.as_ref().map(|why| why.0.clone())
+    }
+
+    fn gen(self, builder: &mut GenModuleBuilder, module: CourseModule) -> &mut GenModuleBuilder {
+        builder
+            .string("description", self.intro)
+            .date("time_modified", self.timemodified);
+
+        let (structure, files): (Vec<Content>, Vec<Content>) = module
+            .contents
+            .into_iter()
+            .partition(|content| content.filename == "structure");
+        let chapters: Vec<Chapter> = structure
+            .first()
+            .and_then(|structure| structure.content.as_deref())
+            .and_then(|chapters| serde_json::from_str(chapters).ok())
+            .unwrap_or_default();
+
+        // chapters are identified by their id, titles dont have to be unique
+        let mut chapter_texts = self.chapter_texts;
+        for chapter in chapters
+            .iter()
+            .flat_map(|chapter| std::iter::once(chapter).chain(chapter.subitems.iter()))
+        {
+            let Some(id) = chapter.id() else {
+                continue;
+            };
+            builder.insert(
+                &format!("chapter:{id}"),
+                EntryValue::Text(chapter.title.clone()),
+            );
+            match chapter_texts.remove(&id) {
+                Some(text) => builder.string(&format!("chapter_text:{id}"), text),
+                // the chapter page couldnt be compared, at least its time is
+                None => {
+                    let folder = format!("/{id}/");
+                    let modified = files
+                        .iter()
+                        .filter(|file| file.filepath.as_deref() == Some(folder.as_str()))
+                        .map(|file| file.timemodified)
+                        .max()
+                        .unwrap_or_default();
+                    builder.date(&format!("chapter_text:{id}"), modified)
+                }
+            };
+        }
+        // pictures and other attachments of the chapters
+        builder.contents(
+            files
+                .into_iter()
+                .filter(|file| file.filename != "index.html")
+                .collect(),
+        )
+    }
+}
+
+impl Chapter {
+    fn id(&self) -> Option<i64> {
+        self.href.split('/').next()?.parse().ok()
+    }
+}
+
+/// the chapter id of the index.html page of a chapter
+fn chapter_id(content: &Content) -> Option<i64> {
+    if content.filename != "index.html" {
+        return None;
+    }
+    content.filepath.as_deref()?.trim_matches('/').parse().ok()
+}
@@ -1,23 +1,26 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-use crate::data::course_contents::{Generate, Id};
+use crate::data::course_contents::{Generate, Id, ModuleKey};
 use crate::data::gen_module::GenModuleBuilder;
+use crate::data::lenient::{skip_invalid, Extra};
 use crate::data::modules::unknown::CourseModule;
-use crate::Moodle;
+use crate::{Moodle, MoodleError};
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct Root {
+    #[serde(deserialize_with = "skip_invalid")]
     pub chats: Vec<Chat>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct Chat {
     //pub id: i64,
     pub coursemodule: i64,
-    //pub course: i64,
+    pub course: i64,
     pub name: String,
     pub intro: String,
     //pub introformat: i64,
@@ -27,23 +30,30 @@ pub struct Chat {
     //pub studentlogs: i64,
     pub chattime: i64,
     //pub schedule: i64,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 impl Id for Chat {
-    fn get_id(&self) -> i64 {
-        self.coursemodule
+    fn get_id(&self) -> ModuleKey {
+        ModuleKey::Module(self.coursemodule)
     }
 }
 
 #[async_trait]
 impl Generate for Chat {
-    async fn request(client: &Moodle, id: i64) -> anyhow::Result<Vec<Self>> {
-        client.get_chats_for_course(id).await
+    const FUNCTION: &'static str = "mod_chat_get_chats_by_courses";
+
+    async fn request_many(
+        client: &Moodle,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Self>>, MoodleError> {
+        client.get_chats_for_courses(course_ids).await
     }
 
     fn gen(self, builder: &mut GenModuleBuilder, _: CourseModule) -> &mut GenModuleBuilder {
         builder
-            .string("Bescheibung", self.intro)
-            .num("Startzeit", self.chattime)
+            .string("description", self.intro)
+            .date("chat_time", self.chattime)
     }
 }
@@ -0,0 +1,117 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::data::course_contents::{request_details, DetailError, Generate, Id, ModuleKey};
+use crate::data::gen_module::GenModuleBuilder;
+use crate::data::lenient::{skip_invalid, Extra};
+use crate::data::modules::unknown::CourseModule;
+use crate::{Moodle, MoodleError};
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Root {
+    #[serde(deserialize_with = "skip_invalid")]
+    pub databases: Vec<Database>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Database {
+    pub id: i64,
+    pub coursemodule: i64,
+    pub course: i64,
+    pub name: String,
+    pub intro: String,
+    //pub introformat: i64,
+    //pub introfiles: Vec<Value>,
+    //pub comments: i64,
+    pub timeavailablefrom: i64,
+    pub timeavailableto: i64,
+    pub timeviewfrom: i64,
+    pub timeviewto: i64,
+    //pub requiredentries: i64,
+    //pub requiredentriestoview: i64,
+    //pub maxentries: i64,
+    //pub rssarticles: i64,
+    //pub singletemplate: String,
+    //pub listtemplate: String,
+    //pub approval: bool,
+    //pub manageapproved: bool,
+    //pub scale: i64,
+    //pub assessed: i64,
+    //pub assesstimestart: i64,
+    //pub assesstimefinish: i64,
+    //pub defaultsort: i64,
+    //pub defaultsortdir: i64,
+    //pub editany: bool,
+    //pub notification: i64,
+    pub timemodified: i64,
+    //pub section: i64,
+    //pub visible: i64,
+    //pub groupmode: i64,
+    //pub groupingid: i64,
+    #[serde(skip)]
+    pub entry_count: Option<i64>,
+    #[serde(skip)]
+    pub failure: Option<DetailError>, // the entries couldnt be counted
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EntriesRoot {
+    pub totalcount: i64,
+}
+
+impl Id for Database {
+    fn get_id(&self) -> ModuleKey {
+        ModuleKey::Module(self.coursemodule)
+    }
+}
+
+#[async_trait]
+impl Generate for Database {
+    const FUNCTION: &'static str = "mod_data_get_databases_by_courses";
+
+    async fn request_many(
+        client: &Moodle,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Self>>, MoodleError> {
+        let mut databases = client.get_databases_for_courses(course_ids).await?;
+        let ids: Vec<i64> = databases.values().flatten().map(|data| data.id).collect();
+        let mut count_by_database = request_details(client, "mod_data_get_entries", ids, |id| {
+            client.get_database_entry_count(id)
+        })
+        .await;
+
+        for database in databases.values_mut().flatten() {
+            match count_by_database.remove(&database.id) {
+                Some(Ok(count)) => database.entry_count = Some(count),
+                Some(Err(why)) => database.failure = Some(why),
+                None => {}
+            }
+        }
+        Ok(databases)
+    }
+
+    fn failure(&self) -> Option<Arc<MoodleError>> {
+        self.failure.as_ref().map(|why| why.0.clone())
+    }
+
+    fn gen(self, builder: &mut GenModuleBuilder, _: CourseModule) -> &mut GenModuleBuilder {
+        builder
+            .string("description", self.intro)
+            .date("time_available_from", self.timeavailablefrom)
+            .date("time_available_to", self.timeavailableto)
+            .date("time_view_from", self.timeviewfrom)
+            .date("time_view_to", self.timeviewto)
+            .date("time_modified", self.timemodified);
+        if let Some(count) = self.entry_count {
+            builder.num("entries", count);
+        }
+        builder
+    }
+}
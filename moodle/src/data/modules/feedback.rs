@@ -1,22 +1,25 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-use crate::data::course_contents::{Generate, Id};
+use crate::data::course_contents::{Generate, Id, ModuleKey};
 use crate::data::gen_module::GenModuleBuilder;
+use crate::data::lenient::{skip_invalid, Extra};
 use crate::data::modules::unknown::CourseModule;
-use crate::Moodle;
+use crate::{Moodle, MoodleError};
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct Root {
+    #[serde(deserialize_with = "skip_invalid")]
     pub feedbacks: Vec<Feedback>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct Feedback {
     //pub id: i64,
-    //pub course: i64,
+    pub course: i64,
     pub name: String,
     pub intro: String,
     //pub introformat: i64,
@@ -31,21 +34,28 @@ pub struct Feedback {
     //pub completionsubmit: bool,
     pub coursemodule: i64,
     //pub introfiles: Vec<Value>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 impl Id for Feedback {
-    fn get_id(&self) -> i64 {
-        self.coursemodule
+    fn get_id(&self) -> ModuleKey {
+        ModuleKey::Module(self.coursemodule)
     }
 }
 
 #[async_trait]
 impl Generate for Feedback {
-    async fn request(client: &Moodle, id: i64) -> anyhow::Result<Vec<Self>> {
-        client.get_feedback_for_course(id).await
+    const FUNCTION: &'static str = "mod_feedback_get_feedbacks_by_courses";
+
+    async fn request_many(
+        client: &Moodle,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Self>>, MoodleError> {
+        client.get_feedback_for_courses(course_ids).await
     }
 
     fn gen(self, builder: &mut GenModuleBuilder, _: CourseModule) -> &mut GenModuleBuilder {
-        builder.string("Bescheibung", self.intro)
+        builder.string("description", self.intro)
     }
 }
@@ -1,23 +1,26 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-use crate::data::course_contents::{Generate, Id};
+use crate::data::course_contents::{Generate, Id, ModuleKey};
 use crate::data::gen_module::GenModuleBuilder;
+use crate::data::lenient::{skip_invalid, Extra};
 use crate::data::modules::unknown::CourseModule;
-use crate::Moodle;
+use crate::{Moodle, MoodleError};
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct Root {
+    #[serde(deserialize_with = "skip_invalid")]
     pub folders: Vec<Folder>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct Folder {
     //pub id: i64,
     pub coursemodule: i64,
-    //pub course: i64,
+    pub course: i64,
     pub name: String,
     pub intro: String,
     //pub introformat: i64,
@@ -32,18 +35,25 @@ pub struct Folder {
     //pub visible: i64,
     //pub groupmode: i64,
     //pub groupingid: i64,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 impl Id for Folder {
-    fn get_id(&self) -> i64 {
-        self.coursemodule
+    fn get_id(&self) -> ModuleKey {
+        ModuleKey::Module(self.coursemodule)
     }
 }
 
 #[async_trait]
 impl Generate for Folder {
-    async fn request(client: &Moodle, id: i64) -> anyhow::Result<Vec<Self>> {
-        client.get_folders_for_course(id).await
+    const FUNCTION: &'static str = "mod_folder_get_folders_by_courses";
+
+    async fn request_many(
+        client: &Moodle,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Self>>, MoodleError> {
+        client.get_folders_for_courses(course_ids).await
     }
 
     fn gen(
@@ -52,9 +62,9 @@ impl Generate for Folder {
         course_module: CourseModule,
     ) -> &mut GenModuleBuilder {
         builder
-            .string("Bescheibung", self.intro)
-            .num("Revision", self.revision)
-            .date("Änderungsdatum", self.timemodified)
+            .string("description", self.intro)
+            .num("revision", self.revision)
+            .date("time_modified", self.timemodified)
             .contents(course_module.contents)
     }
 }
@@ -1,18 +1,20 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-use crate::data::course_contents::{Generate, Id};
+use crate::data::course_contents::{Generate, Id, ModuleKey};
 use crate::data::gen_module::GenModuleBuilder;
+use crate::data::lenient::{Extra, LenientVec};
 use crate::data::modules::unknown::CourseModule;
-use crate::Moodle;
+use crate::{Moodle, MoodleError};
 
-pub type Root = Vec<Forum>;
+pub type Root = LenientVec<Forum>;
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct Forum {
-    //pub id: i64,
-    //pub course: i64,
+    pub id: i64,
+    pub course: i64,
     //pub type_field: String,
     pub name: String,
     pub intro: String,
@@ -46,24 +48,31 @@ pub struct Forum {
     //pub cancreatediscussions: bool,
     //pub lockdiscussionafter: i64,
     //pub istracked: bool,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 impl Id for Forum {
-    fn get_id(&self) -> i64 {
-        self.cmid
+    fn get_id(&self) -> ModuleKey {
+        ModuleKey::Module(self.cmid)
     }
 }
 
 #[async_trait]
 impl Generate for Forum {
-    async fn request(client: &Moodle, id: i64) -> anyhow::Result<Vec<Self>> {
-        client.get_forums_for_course(id).await
+    const FUNCTION: &'static str = "mod_forum_get_forums_by_courses";
+
+    async fn request_many(
+        client: &Moodle,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Self>>, MoodleError> {
+        client.get_forums_for_courses(course_ids).await
     }
 
     fn gen(self, builder: &mut GenModuleBuilder, _: CourseModule) -> &mut GenModuleBuilder {
         builder
-            .string("Bescheibung", self.intro)
-            .num("Anzahl an Disskusionen", self.numdiscussions)
-            .date("Änderungsdatum", self.timemodified)
+            .string("description", self.intro)
+            .num("discussions", self.numdiscussions)
+            .date("time_modified", self.timemodified)
     }
 }
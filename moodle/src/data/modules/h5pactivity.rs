@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::data::course_contents::{Generate, Id, ModuleKey};
+use crate::data::gen_module::GenModuleBuilder;
+use crate::data::lenient::{skip_invalid, Extra};
+use crate::data::modules::unknown::CourseModule;
+use crate::data::other_content::file::FileInfo;
+use crate::{Moodle, MoodleError};
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Root {
+    #[serde(deserialize_with = "skip_invalid")]
+    pub h5pactivities: Vec<H5pActivity>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct H5pActivity {
+    //pub id: i64,
+    pub coursemodule: i64,
+    pub course: i64,
+    pub name: String,
+    pub intro: String,
+    //pub introformat: i64,
+    //pub introfiles: Vec<Value>,
+    //pub timecreated: i64,
+    pub timemodified: i64,
+    //pub grade: i64,
+    //pub displayoptions: i64,
+    //pub enabletracking: i64,
+    //pub grademethod: i64,
+    //pub contenthash: String,
+    #[serde(deserialize_with = "skip_invalid")]
+    pub package: Vec<FileInfo>,
+    //pub deployedfile: DeployedFile,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Id for H5pActivity {
+    fn get_id(&self) -> ModuleKey {
+        ModuleKey::Module(self.coursemodule)
+    }
+}
+
+#[async_trait]
+impl Generate for H5pActivity {
+    const FUNCTION: &'static str = "mod_h5pactivity_get_h5pactivities_by_courses";
+
+    async fn request_many(
+        client: &Moodle,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Self>>, MoodleError> {
+        client.get_h5pactivities_for_courses(course_ids).await
+    }
+
+    fn gen(self, builder: &mut GenModuleBuilder, _: CourseModule) -> &mut GenModuleBuilder {
+        builder
+            .string("description", self.intro)
+            .date("time_modified", self.timemodified)
+            .files(self.package)
+    }
+}
@@ -0,0 +1,100 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::data::course_contents::{Generate, Id, ModuleKey};
+use crate::data::gen_module::GenModuleBuilder;
+use crate::data::lenient::{skip_invalid, Extra};
+use crate::data::modules::unknown::CourseModule;
+use crate::{Moodle, MoodleError};
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Root {
+    #[serde(deserialize_with = "skip_invalid")]
+    pub lessons: Vec<Lesson>,
+}
+
+// moodle only sends most settings to users that can see them, so they default to 0
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Lesson {
+    //pub id: i64,
+    pub coursemodule: i64,
+    pub course: i64,
+    pub name: String,
+    pub intro: String,
+    //pub introformat: i64,
+    //pub introfiles: Vec<Value>,
+    //pub practice: bool,
+    //pub modattempts: bool,
+    //pub usepassword: bool,
+    //pub password: String,
+    //pub dependency: i64,
+    //pub conditions: String,
+    //pub grade: i64,
+    //pub custom: bool,
+    //pub ongoing: bool,
+    //pub usemaxgrade: i64,
+    //pub maxanswers: i64,
+    //pub maxattempts: i64,
+    pub review: bool,
+    //pub nextpagedefault: i64,
+    //pub feedback: bool,
+    //pub minquestions: i64,
+    //pub maxpages: i64,
+    pub timelimit: i64,
+    pub retake: bool,
+    //pub activitylink: i64,
+    //pub mediafile: String,
+    //pub mediaheight: i64,
+    //pub mediawidth: i64,
+    //pub mediaclose: i64,
+    //pub slideshow: bool,
+    //pub width: i64,
+    //pub height: i64,
+    //pub bgcolor: String,
+    //pub displayleft: bool,
+    //pub displayleftif: i64,
+    //pub progressbar: bool,
+    pub available: i64,
+    pub deadline: i64,
+    pub timemodified: i64,
+    //pub completionendreached: i64,
+    //pub completiontimespent: i64,
+    //pub allowofflineattempts: bool,
+    //pub section: i64,
+    //pub visible: i64,
+    //pub groupmode: i64,
+    //pub groupingid: i64,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Id for Lesson {
+    fn get_id(&self) -> ModuleKey {
+        ModuleKey::Module(self.coursemodule)
+    }
+}
+
+#[async_trait]
+impl Generate for Lesson {
+    const FUNCTION: &'static str = "mod_lesson_get_lessons_by_courses";
+
+    async fn request_many(
+        client: &Moodle,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Self>>, MoodleError> {
+        client.get_lessons_for_courses(course_ids).await
+    }
+
+    fn gen(self, builder: &mut GenModuleBuilder, _: CourseModule) -> &mut GenModuleBuilder {
+        builder
+            .string("description", self.intro)
+            .date("time_open", self.available)
+            .date("time_close", self.deadline)
+            .time_limit(self.timelimit)
+            .bool("retake", self.retake)
+            .date("time_modified", self.timemodified)
+    }
+}
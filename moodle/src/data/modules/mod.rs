@@ -1,14 +1,20 @@
 pub mod assignment;
 pub mod bigbluebutton;
+pub mod book;
 pub mod chat;
 pub mod choice;
+pub mod database;
 pub mod feedback;
 pub mod folder;
 pub mod forum;
 pub mod glossary;
+pub mod h5pactivity;
 pub mod label;
+pub mod lesson;
 pub mod page;
 pub mod quiz;
 pub mod resource;
 pub mod unknown;
 pub mod url;
+pub mod wiki;
+pub mod workshop;
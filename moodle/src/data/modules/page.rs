@@ -1,30 +1,34 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-use crate::data::course_contents::{Generate, Id};
+use crate::data::course_contents::{Generate, Id, ModuleKey};
 use crate::data::gen_module::GenModuleBuilder;
+use crate::data::lenient::{skip_invalid, Extra};
 use crate::data::modules::unknown::CourseModule;
 use crate::data::other_content::file::FileInfo;
-use crate::Moodle;
+use crate::{Moodle, MoodleError};
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct Root {
+    #[serde(deserialize_with = "skip_invalid")]
     pub pages: Vec<Page>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct Page {
     //pub id: i64,
     pub coursemodule: i64,
-    //pub course: i64,
+    pub course: i64,
     pub name: String,
     pub intro: String,
     //pub introformat: i64,
     //pub introfiles: Vec<Value>,
     pub content: String,
     //pub contentformat: i64,
+    #[serde(deserialize_with = "skip_invalid")]
     pub contentfiles: Vec<FileInfo>,
     //pub legacyfiles: i64,
     //pub legacyfileslast: Value,
@@ -36,26 +40,33 @@ pub struct Page {
     //pub visible: i64,
     //pub groupmode: i64,
     //pub groupingid: i64,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 impl Id for Page {
-    fn get_id(&self) -> i64 {
-        self.coursemodule
+    fn get_id(&self) -> ModuleKey {
+        ModuleKey::Module(self.coursemodule)
     }
 }
 
 #[async_trait]
 impl Generate for Page {
-    async fn request(client: &Moodle, id: i64) -> anyhow::Result<Vec<Self>> {
-        client.get_pages_for_course(id).await
+    const FUNCTION: &'static str = "mod_page_get_pages_by_courses";
+
+    async fn request_many(
+        client: &Moodle,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Self>>, MoodleError> {
+        client.get_pages_for_courses(course_ids).await
     }
 
     fn gen(self, builder: &mut GenModuleBuilder, _: CourseModule) -> &mut GenModuleBuilder {
         builder
-            .string("Beschreibung", self.intro)
-            .string("Content", self.content)
-            .num("Revision", self.revision)
-            .date("Änderungsdatum", self.timemodified)
+            .string("description", self.intro)
+            .string("content", self.content)
+            .num("revision", self.revision)
+            .date("time_modified", self.timemodified)
             .files(self.contentfiles)
     }
 }
@@ -1,22 +1,25 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-use crate::data::course_contents::{Generate, Id};
+use crate::data::course_contents::{Generate, Id, ModuleKey};
 use crate::data::gen_module::GenModuleBuilder;
+use crate::data::lenient::{skip_invalid, Extra};
 use crate::data::modules::unknown::CourseModule;
-use crate::Moodle;
+use crate::{Moodle, MoodleError};
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct Root {
+    #[serde(deserialize_with = "skip_invalid")]
     pub quizzes: Vec<Quiz>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct Quiz {
     //pub id: i64,
-    //pub course: i64,
+    pub course: i64,
     pub coursemodule: i64,
     pub name: String,
     pub intro: String,
@@ -39,11 +42,14 @@ pub struct Quiz {
     //pub groupingid: i64,
     //#[serde(flatten)]
     //pub additional_data: Option<AdditionalData>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 //some additional info, too lazy to parse it and teachers generally use the defaults
+#[allow(dead_code)]
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 struct AdditionalData {
     //pub overduehandling: String,
     //pub graceperiod: i64,
@@ -71,24 +77,29 @@ struct AdditionalData {
 }
 
 impl Id for Quiz {
-    fn get_id(&self) -> i64 {
-        self.coursemodule
+    fn get_id(&self) -> ModuleKey {
+        ModuleKey::Module(self.coursemodule)
     }
 }
 
 #[async_trait]
 impl Generate for Quiz {
-    async fn request(client: &Moodle, id: i64) -> anyhow::Result<Vec<Self>> {
-        client.get_quizzes_for_course(id).await
+    const FUNCTION: &'static str = "mod_quiz_get_quizzes_by_courses";
+
+    async fn request_many(
+        client: &Moodle,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Self>>, MoodleError> {
+        client.get_quizzes_for_courses(course_ids).await
     }
 
     fn gen(self, builder: &mut GenModuleBuilder, _: CourseModule) -> &mut GenModuleBuilder {
         builder
-            .string("Bescheibung", self.intro)
-            .date("Öffnungsdatum", self.timeopen)
-            .date("Schlussdatum", self.timeclose)
-            .num("Zeitspanne in Sekunden", self.timelimit)
-            .num("Versuche", self.attempts)
-            .num("Fragenanzahl", self.sumgrades)
+            .string("description", self.intro)
+            .date("time_open", self.timeopen)
+            .date("time_close", self.timeclose)
+            .time_limit(self.timelimit)
+            .num("attempts", self.attempts)
+            .num("questions", self.sumgrades)
     }
 }
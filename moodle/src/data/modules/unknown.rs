@@ -1,11 +1,13 @@
-use crate::data::course_contents::{Generate, Id};
-use crate::data::gen_module::{GenModule, GenModuleBuilder};
-use crate::Moodle;
+use crate::data::course_contents::{Generate, Generated, Id, ModuleKey};
+use crate::data::gen_module::{Fallback, GenModule, GenModuleBuilder};
+use crate::data::lenient::{skip_invalid, Extra};
+use crate::{Moodle, MoodleError};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-#[derive(Default, Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
 pub struct CourseModule {
     pub id: i64,             //id
     pub url: Option<String>, //points to segment
@@ -13,58 +15,79 @@ pub struct CourseModule {
     pub modicon: String,
     pub modname: String, //label, resource, feedback, forum, assign, folder, publication, questionaire, url...
     //pub customdata: String, //customcompletionrules and some other stuff
+    #[serde(deserialize_with = "skip_invalid")]
     pub dates: Vec<Date>, //for assignments and publications
-    #[serde(default)]
+    #[serde(deserialize_with = "skip_invalid")]
     pub contents: Vec<Content>,
     pub description: Option<String>, //html description
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
 pub struct Date {
     pub label: String,
     pub timestamp: i64,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
 pub struct Content {
     #[serde(rename = "type")]
     pub type_field: String, //file or url
     pub filename: String,         //self explanatory
+    pub filepath: Option<String>, //folder inside the module, e.g. "/1/" for the first book chapter
     pub filesize: i64,            // filesize in bytes
     pub fileurl: String,          //link to file
     pub timecreated: Option<i64>, //is always present?
     pub timemodified: i64,        //this too
     pub mimetype: Option<String>, //pdf etc
     pub author: Option<String>,   //creator
+    pub content: Option<String>,  //inline content, e.g. the table of contents of a book
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl CourseModule {
+    /// generate the module from the course contents alone
+    pub(crate) fn fallback(self, reason: Fallback) -> GenModule {
+        let mut builder = GenModuleBuilder::new(self.get_id(), self.modicon, self.name, self.url);
+        builder
+            .string("type", self.modname)
+            .string_option("description", self.description);
+        for date in self.dates {
+            builder.date(&date.label, date.timestamp);
+        }
+        builder.contents(self.contents).fallback(reason);
+        builder.build()
+    }
 }
 
 impl Id for CourseModule {
-    fn get_id(&self) -> i64 {
-        self.id
+    fn get_id(&self) -> ModuleKey {
+        ModuleKey::Module(self.id)
     }
 }
 
+/// fallback for module types without a more specific request, everything comes from the course contents
 #[async_trait]
 impl Generate for CourseModule {
-    async fn request(_: &Moodle, _: i64) -> anyhow::Result<Vec<Self>> {
-        unreachable!()
+    const FUNCTION: &'static str = "core_course_get_contents";
+
+    async fn request_many(_: &Moodle, _: &[i64]) -> Result<HashMap<i64, Vec<Self>>, MoodleError> {
+        Ok(HashMap::new())
     }
 
-    async fn process(course_modules: Vec<CourseModule>, _: &Moodle, _: i64) -> anyhow::Result<Vec<GenModule>> {
-        let res = course_modules
+    fn build(course_modules: Vec<CourseModule>, _: Vec<Self>) -> Generated {
+        let modules = course_modules
             .into_iter()
-            .map(|module| {
-                let mut builder =
-                    GenModuleBuilder::new(module.id, module.modicon, module.name, module.url);
-                builder
-                    .string("Type", module.modname)
-                    .contents(module.contents);
-                builder.build()
-            })
+            .map(|module| module.fallback(Fallback::UnknownType))
             .collect();
-        Ok(res)
+        Generated {
+            modules,
+            failed: vec![],
+        }
     }
 
     fn gen(self, _: &mut GenModuleBuilder, _: CourseModule) -> &mut GenModuleBuilder {
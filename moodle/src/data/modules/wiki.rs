@@ -0,0 +1,118 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::data::course_contents::{request_details, DetailError, Generate, Id, ModuleKey};
+use crate::data::gen_module::GenModuleBuilder;
+use crate::data::lenient::{skip_invalid, Extra};
+use crate::data::modules::unknown::CourseModule;
+use crate::{Moodle, MoodleError};
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Root {
+    #[serde(deserialize_with = "skip_invalid")]
+    pub wikis: Vec<Wiki>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Wiki {
+    pub id: i64,
+    pub coursemodule: i64,
+    pub course: i64,
+    pub name: String,
+    pub intro: String,
+    //pub introformat: i64,
+    //pub introfiles: Vec<Value>,
+    //pub timecreated: i64,
+    pub timemodified: i64,
+    pub firstpagetitle: String,
+    pub wikimode: String, // collaborative or individual
+    //pub defaultformat: String,
+    //pub forceformat: i64,
+    //pub editbegin: i64,
+    //pub editend: i64,
+    //pub section: i64,
+    //pub visible: i64,
+    //pub groupmode: i64,
+    //pub groupingid: i64,
+    //pub cancreatepages: bool,
+    #[serde(skip)]
+    pub pages: Option<Vec<WikiPage>>,
+    #[serde(skip)]
+    pub failure: Option<DetailError>, // the pages couldnt be requested
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PagesRoot {
+    #[serde(deserialize_with = "skip_invalid")]
+    pub pages: Vec<WikiPage>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct WikiPage {
+    pub id: i64,
+    //pub subwikiid: i64,
+    pub title: String,
+    //pub timecreated: i64,
+    pub timemodified: i64,
+    //pub timerendered: i64,
+    //pub userid: i64,
+    //pub pageviews: i64,
+    //pub readonly: i64,
+    //pub caneditpage: bool,
+    //pub firstpage: bool,
+}
+
+impl Id for Wiki {
+    fn get_id(&self) -> ModuleKey {
+        ModuleKey::Module(self.coursemodule)
+    }
+}
+
+#[async_trait]
+impl Generate for Wiki {
+    const FUNCTION: &'static str = "mod_wiki_get_wikis_by_courses";
+
+    async fn request_many(
+        client: &Moodle,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Self>>, MoodleError> {
+        let mut wikis = client.get_wikis_for_courses(course_ids).await?;
+        let ids: Vec<i64> = wikis.values().flatten().map(|wiki| wiki.id).collect();
+        let mut pages_by_wiki = request_details(client, "mod_wiki_get_subwiki_pages", ids, |id| {
+            client.get_wiki_pages(id)
+        })
+        .await;
+
+        for wiki in wikis.values_mut().flatten() {
+            match pages_by_wiki.remove(&wiki.id) {
+                Some(Ok(pages)) => wiki.pages = Some(pages),
+                Some(Err(why)) => wiki.failure = Some(why),
+                None => {}
+            }
+        }
+        Ok(wikis)
+    }
+
+    fn failure(&self) -> Option<Arc<MoodleError>> {
+        self.failure.as_ref().map(|why| why.0.clone())
+    }
+
+    fn gen(self, builder: &mut GenModuleBuilder, _: CourseModule) -> &mut GenModuleBuilder {
+        builder
+            .string("description", self.intro)
+            .string("first_page", self.firstpagetitle)
+            .date("time_modified", self.timemodified);
+        for page in self.pages.unwrap_or_default() {
+            builder.date(&format!("page:{}", page.title), page.timemodified);
+        }
+        builder
+    }
+}
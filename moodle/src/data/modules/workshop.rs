@@ -0,0 +1,112 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::data::course_contents::{Generate, Id, ModuleKey};
+use crate::data::gen_module::GenModuleBuilder;
+use crate::data::lenient::{skip_invalid, Extra};
+use crate::data::modules::unknown::CourseModule;
+use crate::{Moodle, MoodleError};
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Root {
+    #[serde(deserialize_with = "skip_invalid")]
+    pub workshops: Vec<Workshop>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Workshop {
+    //pub id: i64,
+    pub coursemodule: i64,
+    pub course: i64,
+    pub name: String,
+    pub intro: String,
+    //pub introformat: i64,
+    //pub introfiles: Vec<Value>,
+    pub instructauthors: String,
+    //pub instructauthorsformat: i64,
+    pub instructreviewers: String,
+    //pub instructreviewersformat: i64,
+    pub timemodified: i64,
+    pub phase: i64,
+    //pub useexamples: bool,
+    //pub usepeerassessment: bool,
+    //pub useselfassessment: bool,
+    //pub grade: f64,
+    //pub gradinggrade: f64,
+    //pub strategy: String,
+    //pub evaluation: String,
+    //pub gradedecimals: i64,
+    //pub submissiontypetext: i64,
+    //pub submissiontypefile: i64,
+    //pub nattachments: i64,
+    //pub submissionfiletypes: String,
+    //pub latesubmissions: bool,
+    //pub maxbytes: i64,
+    //pub examplesmode: i64,
+    pub submissionstart: i64,
+    pub submissionend: i64,
+    pub assessmentstart: i64,
+    pub assessmentend: i64,
+    //pub phaseswitchassessment: bool,
+    //pub conclusion: String,
+    //pub conclusionformat: i64,
+    //pub overallfeedbackmode: i64,
+    //pub overallfeedbackfiles: i64,
+    //pub overallfeedbackfiletypes: String,
+    //pub overallfeedbackmaxbytes: i64,
+    //pub section: i64,
+    //pub visible: i64,
+    //pub groupmode: i64,
+    //pub groupingid: i64,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Workshop {
+    /// label key of the phase
+    fn phase_name(&self) -> &'static str {
+        match self.phase {
+            10 => "workshop_phase_setup",
+            20 => "workshop_phase_submission",
+            30 => "workshop_phase_assessment",
+            40 => "workshop_phase_evaluation",
+            50 => "workshop_phase_closed",
+            _ => "workshop_phase_unknown",
+        }
+    }
+}
+
+impl Id for Workshop {
+    fn get_id(&self) -> ModuleKey {
+        ModuleKey::Module(self.coursemodule)
+    }
+}
+
+#[async_trait]
+impl Generate for Workshop {
+    const FUNCTION: &'static str = "mod_workshop_get_workshops_by_courses";
+
+    async fn request_many(
+        client: &Moodle,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Self>>, MoodleError> {
+        client.get_workshops_for_courses(course_ids).await
+    }
+
+    fn gen(self, builder: &mut GenModuleBuilder, _: CourseModule) -> &mut GenModuleBuilder {
+        let phase = self.phase_name();
+        builder
+            .string("description", self.intro)
+            .label("phase", phase)
+            .date("submission_start", self.submissionstart)
+            .date("submission_end", self.submissionend)
+            .date("assessment_start", self.assessmentstart)
+            .date("assessment_end", self.assessmentend)
+            .string("instruct_authors", self.instructauthors)
+            .string("instruct_reviewers", self.instructreviewers)
+            .date("time_modified", self.timemodified)
+    }
+}
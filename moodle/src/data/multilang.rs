@@ -0,0 +1,121 @@
+use regex::Regex;
+use serde_json::Value;
+use std::sync::OnceLock;
+
+/// Resolve the blocks of moodles multilang filter locally, for sites that dont filter web service
+/// answers. Of consecutive `<span lang="de" class="multilang">` (or old `<lang lang="de">`) blocks
+/// only the one in `language` is kept, otherwise the parent language ("de" for "de_du") or the first
+pub fn resolve(text: &str, language: &str) -> String {
+    let language = normalize(language);
+    let parent = language.split('_').next().unwrap_or_default().to_string();
+
+    let mut resolved = String::with_capacity(text.len());
+    let mut copied = 0;
+    let mut group: Vec<Block> = vec![];
+    for captures in block_regex().captures_iter(text) {
+        let whole = captures.get(0).unwrap();
+        let block = Block {
+            start: whole.start(),
+            end: whole.end(),
+            language: normalize(
+                captures
+                    .name("first")
+                    .or_else(|| captures.name("second"))
+                    .or_else(|| captures.name("old"))
+                    .map_or("", |lang| lang.as_str()),
+            ),
+            text: captures
+                .name("text")
+                .or_else(|| captures.name("old_text"))
+                .map_or("", |text| text.as_str()),
+        };
+        // blocks only belong together if there is nothing but whitespace between them
+        let continues = group
+            .last()
+            .is_some_and(|last| text[last.end..block.start].trim().is_empty());
+        if !continues {
+            copied = flush(text, &mut group, copied, &mut resolved, &language, &parent);
+        }
+        group.push(block);
+    }
+    copied = flush(text, &mut group, copied, &mut resolved, &language, &parent);
+    resolved.push_str(&text[copied..]);
+    resolved
+}
+
+/// resolve every string of a web service answer, answers without multilang blocks are kept as they are
+pub(crate) fn resolve_body(body: String, language: &str) -> String {
+    if !has_blocks(&body) {
+        return body;
+    }
+    let Ok(mut value) = serde_json::from_str::<Value>(&body) else {
+        return body;
+    };
+    resolve_value(&mut value, language);
+    serde_json::to_string(&value).unwrap_or(body)
+}
+
+fn resolve_value(value: &mut Value, language: &str) {
+    match value {
+        Value::String(text) if has_blocks(text) => *text = resolve(text, language),
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| resolve_value(value, language)),
+        Value::Object(map) => map
+            .values_mut()
+            .for_each(|value| resolve_value(value, language)),
+        _ => {}
+    }
+}
+
+fn has_blocks(text: &str) -> bool {
+    text.contains("multilang") || text.contains("<lang")
+}
+
+struct Block<'a> {
+    start: usize,
+    end: usize,
+    language: String,
+    text: &'a str,
+}
+
+/// replace a group of blocks with the text of the chosen language. A single block isnt a choice,
+/// moodle leaves it alone as well. Returns up to where `text` has been copied into `resolved`
+fn flush(
+    text: &str,
+    group: &mut Vec<Block>,
+    copied: usize,
+    resolved: &mut String,
+    language: &str,
+    parent: &str,
+) -> usize {
+    if group.len() < 2 {
+        group.clear();
+        return copied;
+    }
+    let chosen = group
+        .iter()
+        .find(|block| block.language == language)
+        .or_else(|| group.iter().find(|block| block.language == parent))
+        .unwrap_or(&group[0]);
+    resolved.push_str(&text[copied..group[0].start]);
+    resolved.push_str(chosen.text);
+    let end = group.last().unwrap().end;
+    group.clear();
+    end
+}
+
+/// "de-DU" and "de_du" are the same language
+fn normalize(language: &str) -> String {
+    language.trim().to_lowercase().replace('-', "_")
+}
+
+fn block_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(
+            r#"(?is)<span\s+(?:lang="(?P<first>[a-z0-9_-]+)"\s+class="multilang"|class="multilang"\s+lang="(?P<second>[a-z0-9_-]+)")\s*>(?P<text>.*?)</span>|<lang\s+lang="(?P<old>[a-z0-9_-]+)"\s*>(?P<old_text>.*?)</lang>"#,
+        )
+        .unwrap()
+    })
+}
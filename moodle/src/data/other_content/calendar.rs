@@ -0,0 +1,162 @@
+use crate::data::lenient::{skip_invalid, Extra};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A calendar event, e.g. the due date of an assignment
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarEvent {
+    pub id: i64,
+    pub name: String,
+    pub course_id: Option<i64>,
+    /// modname of the activity the event belongs to, e.g. "assign"
+    pub module_name: Option<String>,
+    /// id of the activity instance (not the course module), e.g. the id of an Assignment
+    pub instance: Option<i64>,
+    /// id of the course module, like the ids of the scanned modules. Only known for action events
+    pub module_id: Option<i64>,
+    /// "due", "open", "close", "course", "user", "site", ...
+    pub event_type: String,
+    /// unix timestamp
+    pub start: i64,
+    /// seconds, 0 for events without a duration
+    pub duration: i64,
+    /// where the user can act on the event, e.g. the submission page, only set for action events
+    pub action_url: Option<String>,
+    /// moodle's own flag for action events, a passed due/close date for other events
+    pub overdue: bool,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl CalendarEvent {
+    /// unix timestamp at which the event is over
+    pub fn end(&self) -> i64 {
+        self.start + self.duration
+    }
+}
+
+/// Answer of core_calendar_get_action_events_by_timesort and _by_course
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct ActionEvents {
+    #[serde(deserialize_with = "skip_invalid")]
+    pub events: Vec<ActionEvent>,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct ActionEvent {
+    id: i64,
+    name: String,
+    modulename: Option<String>,
+    instance: Option<i64>,
+    eventtype: String,
+    timestart: i64,
+    timeduration: i64,
+    overdue: bool,
+    course: Option<EventCourse>,
+    action: Option<EventAction>,
+    /// only sent by newer moodle versions
+    course_module_id: Option<i64>,
+    /// the page of the activity, e.g. .../mod/assign/view.php?id=103
+    url: Option<String>,
+    #[serde(flatten)]
+    extra: Extra,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+struct EventCourse {
+    id: i64,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+struct EventAction {
+    url: String,
+}
+
+impl From<ActionEvent> for CalendarEvent {
+    fn from(event: ActionEvent) -> Self {
+        CalendarEvent {
+            id: event.id,
+            name: event.name,
+            course_id: event.course.map(|course| course.id),
+            module_name: event.modulename.filter(|name| !name.is_empty()),
+            instance: event.instance.filter(|instance| *instance != 0),
+            module_id: event
+                .course_module_id
+                .or_else(|| event.url.as_deref().and_then(module_id_from_url))
+                .filter(|id| *id != 0),
+            event_type: event.eventtype,
+            start: event.timestart,
+            duration: event.timeduration,
+            action_url: event.action.map(|action| action.url),
+            overdue: event.overdue,
+            extra: event.extra,
+        }
+    }
+}
+
+/// the `id` parameter of an activity page, which is the course module id
+fn module_id_from_url(url: &str) -> Option<i64> {
+    if !url.contains("/mod/") {
+        return None;
+    }
+    let (_, query) = url.split_once('?')?;
+    query
+        .split('&')
+        .find_map(|param| param.strip_prefix("id="))?
+        .parse()
+        .ok()
+}
+
+/// Answer of core_calendar_get_calendar_events
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct Events {
+    #[serde(deserialize_with = "skip_invalid")]
+    pub events: Vec<Event>,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct Event {
+    id: i64,
+    name: String,
+    courseid: i64,
+    modulename: Option<String>,
+    instance: Option<i64>,
+    eventtype: String,
+    timestart: i64,
+    timeduration: i64,
+    #[serde(flatten)]
+    extra: Extra,
+}
+
+impl From<Event> for CalendarEvent {
+    fn from(event: Event) -> Self {
+        // this function doesnt report overdue events, so a passed deadline has to do
+        let deadline = matches!(event.eventtype.as_str(), "due" | "close");
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs() as i64)
+            .unwrap_or_default();
+        CalendarEvent {
+            id: event.id,
+            name: event.name,
+            // site and user events belong to the front page (course 1) or no course at all
+            course_id: Some(event.courseid).filter(|id| *id > 1),
+            module_name: event.modulename.filter(|name| !name.is_empty()),
+            instance: event.instance.filter(|instance| *instance != 0),
+            module_id: None,
+            event_type: event.eventtype,
+            start: event.timestart,
+            duration: event.timeduration,
+            action_url: None,
+            overdue: deadline && event.timestart + event.timeduration < now,
+            extra: event.extra,
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::data::lenient::{skip_invalid, Extra};
+use serde::{Deserialize, Serialize};
+
+/// Completion of a single activity for the token's user
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActivityCompletion {
+    /// course module id
+    pub module_id: i64,
+    /// e.g. "assign"
+    pub module_name: String,
+    pub instance: i64,
+    pub state: CompletionState,
+    pub tracking: CompletionTracking,
+    /// unix timestamp, None while the activity is incomplete
+    pub time_completed: Option<i64>,
+    /// id of the teacher that overrode the state
+    pub overridden_by: Option<i64>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl ActivityCompletion {
+    pub fn is_complete(&self) -> bool {
+        self.state != CompletionState::Incomplete
+    }
+
+    /// only manually tracked activities can be marked as done by the user
+    pub fn is_manual(&self) -> bool {
+        self.tracking == CompletionTracking::Manual
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompletionState {
+    #[default]
+    Incomplete,
+    Complete,
+    /// completed with a passing grade
+    CompletePass,
+    /// completed, but with a failing grade
+    CompleteFail,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompletionTracking {
+    #[default]
+    None,
+    /// the user ticks the activity off
+    Manual,
+    /// moodle completes the activity once its conditions are met
+    Automatic,
+}
+
+/// Answer of core_completion_get_activities_completion_status
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct Statuses {
+    #[serde(deserialize_with = "skip_invalid")]
+    pub statuses: Vec<RawStatus>,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct RawStatus {
+    cmid: i64,
+    modname: String,
+    instance: i64,
+    state: i64,
+    timecompleted: i64,
+    tracking: i64,
+    overrideby: Option<i64>,
+    #[serde(flatten)]
+    extra: Extra,
+}
+
+impl From<RawStatus> for ActivityCompletion {
+    fn from(status: RawStatus) -> Self {
+        ActivityCompletion {
+            module_id: status.cmid,
+            module_name: status.modname,
+            instance: status.instance,
+            state: match status.state {
+                1 => CompletionState::Complete,
+                2 => CompletionState::CompletePass,
+                3 => CompletionState::CompleteFail,
+                _ => CompletionState::Incomplete,
+            },
+            tracking: match status.tracking {
+                1 => CompletionTracking::Manual,
+                2 => CompletionTracking::Automatic,
+                _ => CompletionTracking::None,
+            },
+            time_completed: Some(status.timecompleted).filter(|time| *time != 0),
+            overridden_by: status.overrideby,
+            extra: status.extra,
+        }
+    }
+}
+
+/// Answer of core_completion_update_activity_completion_status_manually
+#[derive(Debug, Deserialize)]
+pub(crate) struct UpdateStatus {
+    pub status: bool,
+}
@@ -1,11 +1,15 @@
+use crate::data::lenient::{skip_invalid, Extra};
 use serde::{Deserialize, Serialize};
 
 #[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
 pub struct Data {
+    #[serde(deserialize_with = "skip_invalid")]
     pub courses: Vec<Course>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Course {
     pub id: i64,
     pub fullname: String,
@@ -16,4 +20,6 @@ pub struct Course {
     pub viewurl: String,
     pub courseimage: String,
     pub coursecategory: String,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
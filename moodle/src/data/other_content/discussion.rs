@@ -0,0 +1,174 @@
+use crate::data::lenient::{skip_invalid, Extra};
+use crate::data::other_content::file::FileInfo;
+use serde::{Deserialize, Serialize};
+
+/// A thread in a forum, together with its first post
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Discussion {
+    pub id: i64,
+    pub forum_id: i64,
+    /// id of the post that started the discussion
+    pub first_post_id: i64,
+    pub subject: String,
+    pub author: String,
+    pub author_picture_url: Option<String>,
+    /// html
+    pub message: String,
+    #[serde(deserialize_with = "skip_invalid")]
+    pub attachments: Vec<FileInfo>,
+    pub created: i64,
+    /// time of the latest post
+    pub modified: i64,
+    pub num_replies: i64,
+    pub pinned: bool,
+    pub link: String,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// A single post of a discussion
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ForumPost {
+    pub id: i64,
+    pub discussion_id: i64,
+    /// None for the first post of a discussion
+    pub parent_id: Option<i64>,
+    pub subject: String,
+    pub author: String,
+    pub author_picture_url: Option<String>,
+    /// html
+    pub message: String,
+    #[serde(deserialize_with = "skip_invalid")]
+    pub attachments: Vec<FileInfo>,
+    pub created: i64,
+    pub link: Option<String>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Answer of mod_forum_get_forum_discussions
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct Discussions {
+    #[serde(deserialize_with = "skip_invalid")]
+    pub discussions: Vec<RawDiscussion>,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct RawDiscussion {
+    id: i64,
+    discussion: i64,
+    subject: String,
+    message: String,
+    #[serde(deserialize_with = "skip_invalid")]
+    attachments: Vec<FileInfo>,
+    created: i64,
+    timemodified: i64,
+    numreplies: i64,
+    pinned: bool,
+    userfullname: Option<String>,
+    userpictureurl: Option<String>,
+    #[serde(flatten)]
+    extra: Extra,
+}
+
+impl RawDiscussion {
+    pub fn into_discussion(self, base: &str, forum_id: i64) -> Discussion {
+        Discussion {
+            id: self.discussion,
+            forum_id,
+            first_post_id: self.id,
+            subject: self.subject,
+            author: self.userfullname.unwrap_or_default(),
+            author_picture_url: self.userpictureurl,
+            message: self.message,
+            attachments: self.attachments,
+            created: self.created,
+            modified: self.timemodified,
+            num_replies: self.numreplies,
+            pinned: self.pinned,
+            link: format!("{base}/mod/forum/discuss.php?d={}", self.discussion),
+            extra: self.extra,
+        }
+    }
+}
+
+/// Answer of mod_forum_get_discussion_posts
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct Posts {
+    #[serde(deserialize_with = "skip_invalid")]
+    pub posts: Vec<RawPost>,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct RawPost {
+    id: i64,
+    discussionid: i64,
+    hasparent: bool,
+    parentid: Option<i64>,
+    subject: String,
+    message: String,
+    timecreated: i64,
+    author: Author,
+    #[serde(deserialize_with = "skip_invalid")]
+    attachments: Vec<Attachment>,
+    urls: Option<PostUrls>,
+    #[serde(flatten)]
+    extra: Extra,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+struct Author {
+    fullname: Option<String>,
+    urls: Option<AuthorUrls>,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+struct AuthorUrls {
+    profileimage: Option<String>,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+struct Attachment {
+    filename: String,
+    url: String,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+struct PostUrls {
+    view: Option<String>,
+}
+
+impl From<RawPost> for ForumPost {
+    fn from(post: RawPost) -> Self {
+        ForumPost {
+            id: post.id,
+            discussion_id: post.discussionid,
+            parent_id: post.parentid.filter(|_| post.hasparent),
+            subject: post.subject,
+            author: post.author.fullname.unwrap_or_default(),
+            author_picture_url: post.author.urls.and_then(|urls| urls.profileimage),
+            message: post.message,
+            attachments: post
+                .attachments
+                .into_iter()
+                .map(|attachment| FileInfo {
+                    filename: attachment.filename,
+                    fileurl: attachment.url,
+                })
+                .collect(),
+            created: post.timecreated,
+            link: post.urls.and_then(|urls| urls.view),
+            extra: post.extra,
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use crate::data::lenient::{skip_invalid, Extra};
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// The final grade of a course, as shown in the grade overview
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CourseGrade {
+    pub course_id: i64,
+    /// formatted like the site displays grades, e.g. "12,50" or "B+"
+    pub grade: Option<String>,
+    pub raw_grade: Option<f64>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// A single entry of a user's grade report, e.g. an assignment or the course total
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GradeItem {
+    pub id: i64,
+    pub name: String,
+    /// "mod", "category", "course" or "manual"
+    pub item_type: String,
+    /// modname of the graded activity, e.g. "assign"
+    pub module_name: Option<String>,
+    /// course module id of the graded activity
+    pub module_id: Option<i64>,
+    pub grade: Option<f64>,
+    /// formatted like the site displays grades, e.g. "12,50" or "B+"
+    pub grade_formatted: Option<String>,
+    pub min: f64,
+    pub max: f64,
+    /// 0-100, None if the item isnt graded yet
+    pub percentage: Option<f64>,
+    /// html
+    pub feedback: Option<String>,
+    /// unix timestamp
+    pub graded: Option<i64>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Answer of gradereport_overview_get_course_grades
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct CourseGrades {
+    #[serde(deserialize_with = "skip_invalid")]
+    pub grades: Vec<RawCourseGrade>,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct RawCourseGrade {
+    courseid: i64,
+    grade: Option<String>,
+    #[serde(deserialize_with = "number_string")]
+    rawgrade: Option<f64>,
+    #[serde(flatten)]
+    extra: Extra,
+}
+
+impl From<RawCourseGrade> for CourseGrade {
+    fn from(grade: RawCourseGrade) -> Self {
+        CourseGrade {
+            course_id: grade.courseid,
+            grade: grade.grade.filter(|grade| grade != "-"),
+            raw_grade: grade.rawgrade,
+            extra: grade.extra,
+        }
+    }
+}
+
+/// Answer of gradereport_user_get_grade_items
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct UserGrades {
+    #[serde(deserialize_with = "skip_invalid")]
+    pub usergrades: Vec<UserGrade>,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct UserGrade {
+    #[serde(deserialize_with = "skip_invalid")]
+    pub gradeitems: Vec<RawGradeItem>,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct RawGradeItem {
+    id: i64,
+    itemname: Option<String>,
+    itemtype: String,
+    itemmodule: Option<String>,
+    cmid: Option<i64>,
+    graderaw: Option<f64>,
+    gradeformatted: Option<String>,
+    grademin: f64,
+    grademax: f64,
+    gradedategraded: Option<i64>,
+    feedback: Option<String>,
+    #[serde(flatten)]
+    extra: Extra,
+}
+
+impl From<RawGradeItem> for GradeItem {
+    fn from(item: RawGradeItem) -> Self {
+        let percentage = match item.graderaw {
+            Some(grade) if item.grademax > item.grademin => {
+                Some((grade - item.grademin) / (item.grademax - item.grademin) * 100.0)
+            }
+            _ => None,
+        };
+        let name = match item.itemname {
+            Some(name) if !name.is_empty() => name,
+            // the course total has no name of its own
+            _ => item.itemtype.clone(),
+        };
+        GradeItem {
+            id: item.id,
+            name,
+            item_type: item.itemtype,
+            module_name: item.itemmodule,
+            module_id: item.cmid,
+            grade: item.graderaw,
+            grade_formatted: item
+                .gradeformatted
+                .filter(|grade| !grade.is_empty() && grade != "-"),
+            min: item.grademin,
+            max: item.grademax,
+            percentage,
+            feedback: item.feedback.filter(|feedback| !feedback.is_empty()),
+            graded: item.gradedategraded,
+            extra: item.extra,
+        }
+    }
+}
+
+/// rawgrade is sent as a string like "12.50000"
+fn number_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    let string: Option<String> = Option::deserialize(deserializer)?;
+    Ok(string.and_then(|string| string.parse().ok()))
+}
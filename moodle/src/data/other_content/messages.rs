@@ -0,0 +1,206 @@
+use crate::data::lenient::{skip_invalid, Extra};
+use serde::{Deserialize, Serialize};
+
+/// A popup notification, e.g. about a graded assignment or a new forum post
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Notification {
+    pub id: i64,
+    pub subject: String,
+    /// html
+    pub message: String,
+    pub url: Option<String>,
+    /// plugin that sent the notification, e.g. "mod_assign"
+    pub component: Option<String>,
+    pub created: i64,
+    pub read: bool,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// A private conversation or a group chat of the token's user
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Conversation {
+    pub id: i64,
+    /// the group name, or the names of the other members for private conversations
+    pub name: String,
+    #[serde(deserialize_with = "skip_invalid")]
+    pub members: Vec<ConversationMember>,
+    pub unread_count: i64,
+    /// id of the newest message
+    pub last_message_id: Option<i64>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConversationMember {
+    pub id: i64,
+    pub fullname: String,
+}
+
+/// A message inside a conversation
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConversationMessage {
+    pub id: i64,
+    pub conversation_id: i64,
+    pub author_id: i64,
+    pub author: Option<String>,
+    /// html
+    pub text: String,
+    pub created: i64,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Answer of message_popup_get_popup_notifications
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct Notifications {
+    #[serde(deserialize_with = "skip_invalid")]
+    pub notifications: Vec<RawNotification>,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct RawNotification {
+    id: i64,
+    subject: String,
+    fullmessagehtml: Option<String>,
+    smallmessage: Option<String>,
+    fullmessage: Option<String>,
+    contexturl: Option<String>,
+    component: Option<String>,
+    timecreated: i64,
+    read: bool,
+    #[serde(flatten)]
+    extra: Extra,
+}
+
+impl From<RawNotification> for Notification {
+    fn from(notification: RawNotification) -> Self {
+        // not every plugin fills every variant of the message
+        let message = [
+            notification.fullmessagehtml,
+            notification.smallmessage,
+            notification.fullmessage,
+        ]
+        .into_iter()
+        .flatten()
+        .find(|message| !message.is_empty())
+        .unwrap_or_default();
+        Notification {
+            id: notification.id,
+            subject: notification.subject,
+            message,
+            url: notification.contexturl.filter(|url| !url.is_empty()),
+            component: notification.component,
+            created: notification.timecreated,
+            read: notification.read,
+            extra: notification.extra,
+        }
+    }
+}
+
+/// Answer of core_message_get_conversations
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct Conversations {
+    #[serde(deserialize_with = "skip_invalid")]
+    pub conversations: Vec<RawConversation>,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct RawConversation {
+    id: i64,
+    name: Option<String>,
+    unreadcount: Option<i64>,
+    #[serde(deserialize_with = "skip_invalid")]
+    members: Vec<ConversationMember>,
+    #[serde(deserialize_with = "skip_invalid")]
+    messages: Vec<RawMessage>,
+    #[serde(flatten)]
+    extra: Extra,
+}
+
+impl From<RawConversation> for Conversation {
+    fn from(conversation: RawConversation) -> Self {
+        let name = match conversation.name {
+            Some(name) if !name.is_empty() => name,
+            // private conversations dont have a name, the member list only contains the other user
+            _ => conversation
+                .members
+                .iter()
+                .map(|member| member.fullname.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        };
+        Conversation {
+            id: conversation.id,
+            name,
+            unread_count: conversation.unreadcount.unwrap_or_default(),
+            last_message_id: conversation.messages.iter().map(|message| message.id).max(),
+            members: conversation.members,
+            extra: conversation.extra,
+        }
+    }
+}
+
+/// Answer of core_message_get_conversation_messages
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct Messages {
+    pub id: i64,
+    #[serde(deserialize_with = "skip_invalid")]
+    pub members: Vec<ConversationMember>,
+    #[serde(deserialize_with = "skip_invalid")]
+    pub messages: Vec<RawMessage>,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct RawMessage {
+    id: i64,
+    useridfrom: i64,
+    text: String,
+    timecreated: i64,
+    #[serde(flatten)]
+    extra: Extra,
+}
+
+impl Messages {
+    pub fn into_messages(self) -> Vec<ConversationMessage> {
+        let Messages {
+            id,
+            members,
+            messages,
+        } = self;
+        messages
+            .into_iter()
+            .map(|message| ConversationMessage {
+                id: message.id,
+                conversation_id: id,
+                author_id: message.useridfrom,
+                author: members
+                    .iter()
+                    .find(|member| member.id == message.useridfrom)
+                    .map(|member| member.fullname.clone()),
+                text: message.text,
+                created: message.timecreated,
+                extra: message.extra,
+            })
+            .collect()
+    }
+}
+
+/// Answer of core_message_send_instant_messages, one entry per message
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct SentMessage {
+    pub msgid: i64,
+    pub errormessage: Option<String>,
+}
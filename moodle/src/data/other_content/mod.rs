@@ -1,3 +1,10 @@
+pub mod calendar;
+pub mod completion;
 pub mod courses;
+pub mod discussion;
 pub mod file;
+pub mod grades;
+pub mod messages;
 pub mod section;
+pub mod site_info;
+pub mod submission_status;
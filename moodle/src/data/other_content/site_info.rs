@@ -0,0 +1,35 @@
+use crate::data::lenient::{skip_invalid, Extra};
+use serde::{Deserialize, Serialize};
+
+/// Answer of core_webservice_get_site_info: who the token belongs to and what it may do
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SiteInfo {
+    pub sitename: String,
+    pub siteurl: String,
+    pub release: String, // e.g. "4.1.2 (Build: 20230313)"
+    pub userid: i64,
+    pub username: String,
+    pub fullname: String,
+    pub lang: String,
+    #[serde(deserialize_with = "skip_invalid")]
+    pub functions: Vec<Function>, // web service functions of the token's service
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Function {
+    pub name: String,
+    pub version: String,
+}
+
+impl SiteInfo {
+    /// true if the token's service includes the web service function
+    pub fn allows(&self, function: &str) -> bool {
+        self.functions
+            .iter()
+            .any(|allowed| allowed.name == function)
+    }
+}
@@ -0,0 +1,97 @@
+use crate::data::lenient::{skip_invalid, Extra};
+use serde::{Deserialize, Serialize};
+
+/// How far the token's user got with an assignment
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubmissionStatus {
+    /// "new", "draft", "submitted" or "reopened", None if there is no submission at all
+    pub status: Option<String>,
+    pub last_modified: Option<i64>,
+    /// "graded" or "notgraded" (or a marking workflow state)
+    pub grading_status: Option<String>,
+    /// formatted like the site displays grades, e.g. "80,00 / 100,00"
+    pub grade: Option<String>,
+    /// html
+    pub feedback_comments: Option<String>,
+    pub extension_due_date: Option<i64>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Answer of mod_assign_get_submission_status
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub(crate) struct StatusRoot {
+    pub lastattempt: Option<LastAttempt>,
+    pub feedback: Option<Feedback>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub(crate) struct LastAttempt {
+    pub submission: Option<Submission>,
+    pub teamsubmission: Option<Submission>,
+    pub extensionduedate: Option<i64>,
+    pub gradingstatus: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub(crate) struct Submission {
+    pub status: String,
+    pub timemodified: i64,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub(crate) struct Feedback {
+    pub gradefordisplay: Option<String>,
+    #[serde(deserialize_with = "skip_invalid")]
+    pub plugins: Vec<FeedbackPlugin>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub(crate) struct FeedbackPlugin {
+    #[serde(rename = "type")]
+    pub type_field: String,
+    #[serde(deserialize_with = "skip_invalid")]
+    pub editorfields: Vec<EditorField>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub(crate) struct EditorField {
+    pub text: String,
+}
+
+impl From<StatusRoot> for SubmissionStatus {
+    fn from(root: StatusRoot) -> Self {
+        let attempt = root.lastattempt.unwrap_or_default();
+        let submission = attempt.submission.or(attempt.teamsubmission);
+        let feedback = root.feedback.unwrap_or_default();
+        let comments = feedback
+            .plugins
+            .into_iter()
+            .filter(|plugin| plugin.type_field == "comments")
+            .flat_map(|plugin| plugin.editorfields)
+            .map(|field| field.text)
+            .find(|text| !text.is_empty());
+        SubmissionStatus {
+            status: submission
+                .as_ref()
+                .map(|submission| submission.status.clone()),
+            last_modified: submission
+                .map(|submission| submission.timemodified)
+                .filter(|time| *time != 0),
+            grading_status: attempt.gradingstatus,
+            grade: feedback.gradefordisplay.filter(|grade| !grade.is_empty()),
+            feedback_comments: comments,
+            extension_due_date: attempt.extensionduedate.filter(|date| *date != 0),
+            extra: root.extra,
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use crate::data::course_contents::{Generate, Generated};
+use crate::data::gen_module::Fallback;
+use crate::data::modules::assignment::Assignment;
+use crate::data::modules::bigbluebutton::Bigbluebuttonbn;
+use crate::data::modules::book::Book;
+use crate::data::modules::chat::Chat;
+use crate::data::modules::choice::Choice;
+use crate::data::modules::database::Database;
+use crate::data::modules::feedback::Feedback;
+use crate::data::modules::folder::Folder;
+use crate::data::modules::forum::Forum;
+use crate::data::modules::glossary::Glossary;
+use crate::data::modules::h5pactivity::H5pActivity;
+use crate::data::modules::label::Label;
+use crate::data::modules::lesson::Lesson;
+use crate::data::modules::page::Page;
+use crate::data::modules::quiz::Quiz;
+use crate::data::modules::resource::Resource;
+use crate::data::modules::unknown::CourseModule;
+use crate::data::modules::url::Url;
+use crate::data::modules::wiki::Wiki;
+use crate::data::modules::workshop::Workshop;
+use crate::data::other_content::site_info::SiteInfo;
+use crate::{Moodle, MoodleError};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// Maps the modname of a course module (e.g. "quiz") to the type that generates it.
+/// `default()` knows every module type of this crate, `new()` none
+#[derive(Clone)]
+pub struct ModuleRegistry {
+    handlers: HashMap<String, Arc<dyn Handler>>,
+}
+
+impl ModuleRegistry {
+    /// a registry without handlers, every module is generated from the course contents alone
+    pub fn new() -> ModuleRegistry {
+        ModuleRegistry {
+            handlers: HashMap::new(),
+        }
+    }
+
+    /// generate the modules with this modname through `T`, replaces a previous handler
+    pub fn register<T: Generate + 'static>(mut self, modname: &str) -> ModuleRegistry {
+        let handler: Arc<dyn Handler> = Arc::new(GenerateHandler::<T>(PhantomData));
+        self.handlers.insert(modname.to_string(), handler);
+        self
+    }
+
+    /// the module types that have a handler
+    pub fn modnames(&self) -> impl Iterator<Item = &str> {
+        self.handlers.keys().map(String::as_str)
+    }
+
+    pub fn contains(&self, modname: &str) -> bool {
+        self.handlers.contains_key(modname)
+    }
+
+    /// generate the modules of one module type (course id -> course modules).
+    /// Without site info every function is assumed to be allowed
+    pub(crate) async fn process(
+        &self,
+        modname: &str,
+        course_modules: HashMap<i64, Vec<CourseModule>>,
+        client: &Moodle,
+        site_info: Option<&SiteInfo>,
+    ) -> Result<HashMap<i64, Generated>, MoodleError> {
+        let Some(handler) = self.handlers.get(modname) else {
+            return Ok(fallback(course_modules, Fallback::UnknownType));
+        };
+        match site_info {
+            // use the generic course module if the token isnt allowed to query the module type
+            Some(site_info) if !site_info.allows(handler.function()) => {
+                Ok(fallback(course_modules, Fallback::NotInService))
+            }
+            _ => handler.process_many(course_modules, client).await,
+        }
+    }
+}
+
+/// the registry that `get_course_info` uses, extend it to support custom plugins
+impl Default for ModuleRegistry {
+    fn default() -> ModuleRegistry {
+        ModuleRegistry::new()
+            .register::<Assignment>("assign")
+            .register::<Bigbluebuttonbn>("bigbluebuttonbn")
+            .register::<Book>("book")
+            .register::<Chat>("chat")
+            .register::<Choice>("choice")
+            .register::<Choice>("questionnaire")
+            .register::<Database>("data")
+            .register::<Feedback>("feedback")
+            .register::<Folder>("folder")
+            .register::<Forum>("forum")
+            .register::<Glossary>("glossary")
+            .register::<H5pActivity>("h5pactivity")
+            .register::<Label>("label")
+            .register::<Lesson>("lesson")
+            .register::<Page>("page")
+            .register::<Quiz>("quiz")
+            .register::<Resource>("resource")
+            .register::<Url>("url")
+            .register::<Wiki>("wiki")
+            .register::<Workshop>("workshop")
+    }
+}
+
+fn fallback(
+    course_modules: HashMap<i64, Vec<CourseModule>>,
+    reason: Fallback,
+) -> HashMap<i64, Generated> {
+    course_modules
+        .into_iter()
+        .map(|(course_id, modules)| {
+            let modules = modules
+                .into_iter()
+                .map(|module| module.fallback(reason))
+                .collect();
+            let generated = Generated {
+                modules,
+                failed: vec![],
+            };
+            (course_id, generated)
+        })
+        .collect()
+}
+
+/// object safe version of Generate, so different module types fit into one map
+#[async_trait]
+trait Handler: Send + Sync {
+    fn function(&self) -> &'static str;
+
+    async fn process_many(
+        &self,
+        course_modules: HashMap<i64, Vec<CourseModule>>,
+        client: &Moodle,
+    ) -> Result<HashMap<i64, Generated>, MoodleError>;
+}
+
+struct GenerateHandler<T>(PhantomData<fn() -> T>);
+
+#[async_trait]
+impl<T: Generate + 'static> Handler for GenerateHandler<T> {
+    fn function(&self) -> &'static str {
+        T::FUNCTION
+    }
+
+    async fn process_many(
+        &self,
+        course_modules: HashMap<i64, Vec<CourseModule>>,
+        client: &Moodle,
+    ) -> Result<HashMap<i64, Generated>, MoodleError> {
+        T::process_many(course_modules, client).await
+    }
+}
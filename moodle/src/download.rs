@@ -0,0 +1,135 @@
+use crate::{send, Moodle, MoodleError};
+use reqwest::header::{HeaderMap, CONTENT_DISPOSITION, CONTENT_TYPE};
+use std::path::Path;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// What the server told about a downloaded file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Download {
+    /// from the Content-Disposition header, or the last segment of the link
+    pub filename: Option<String>,
+    /// the Content-Type header
+    pub mime_type: Option<String>,
+    /// number of bytes that were written
+    pub size: u64,
+}
+
+impl Moodle {
+    /// download a file into memory, fails with [MoodleError::TooLarge] once it gets larger than `max_size`
+    pub async fn download_file(&self, link: String, max_size: u64) -> Result<Vec<u8>, MoodleError> {
+        let mut file = Vec::new();
+        self.download_to(&link, max_size, &mut file).await?;
+        Ok(file)
+    }
+
+    /// stream a file into `writer` without holding it in memory.
+    /// Bytes that were already written stay in the writer if the download fails
+    pub async fn download_to<W: AsyncWrite + Unpin>(
+        &self,
+        link: &str,
+        max_size: u64,
+        writer: &mut W,
+    ) -> Result<Download, MoodleError> {
+        let url = if link.contains('?') {
+            format!("{}&token={}", link, self.token)
+        } else {
+            format!("{}?token={}", link, self.token)
+        };
+        // the permit is held until the whole file is read
+        let _permit = self.permit().await;
+        // only the request itself is retried, a half written file cant be taken back
+        let mut response = self
+            .retry
+            .run(link, true, || send(&self.retry, self.http.get(&url)))
+            .await?;
+        if let Some(length) = response.content_length() {
+            if length > max_size {
+                return Err(MoodleError::TooLarge {
+                    size: length,
+                    max_size,
+                });
+            }
+        }
+        let mut download = Download {
+            filename: filename(response.headers()).or_else(|| filename_from_link(link)),
+            mime_type: header(response.headers(), CONTENT_TYPE),
+            size: 0,
+        };
+
+        // chunked answers dont announce a length, so the limit is checked while reading
+        while let Some(chunk) = response.chunk().await? {
+            download.size += chunk.len() as u64;
+            if download.size > max_size {
+                return Err(MoodleError::TooLarge {
+                    size: download.size,
+                    max_size,
+                });
+            }
+            writer.write_all(&chunk).await?;
+        }
+        writer.flush().await?;
+        Ok(download)
+    }
+
+    /// stream a file to `path`, the file is removed again if the download fails
+    pub async fn download_to_path(
+        &self,
+        link: &str,
+        max_size: u64,
+        path: impl AsRef<Path>,
+    ) -> Result<Download, MoodleError> {
+        let path = path.as_ref();
+        let mut file = tokio::fs::File::create(path).await?;
+        let result = self.download_to(link, max_size, &mut file).await;
+        if result.is_err() {
+            drop(file);
+            let _ = tokio::fs::remove_file(path).await;
+        }
+        result
+    }
+}
+
+fn header(headers: &HeaderMap, name: reqwest::header::HeaderName) -> Option<String> {
+    headers.get(name)?.to_str().ok().map(String::from)
+}
+
+/// read the filename out of `Content-Disposition: attachment; filename="name.pdf"`,
+/// preferring the utf-8 variant `filename*=UTF-8''name.pdf`
+fn filename(headers: &HeaderMap) -> Option<String> {
+    let disposition = header(headers, CONTENT_DISPOSITION)?;
+    let mut plain = None;
+    for part in disposition.split(';').map(str::trim) {
+        if let Some(encoded) = part.strip_prefix("filename*=") {
+            let encoded = encoded.splitn(3, '\'').nth(2).unwrap_or(encoded);
+            if let Some(name) = percent_decode(encoded) {
+                return Some(name);
+            }
+        } else if let Some(name) = part.strip_prefix("filename=") {
+            plain = Some(name.trim_matches('"').to_string());
+        }
+    }
+    plain.filter(|name| !name.is_empty())
+}
+
+fn filename_from_link(link: &str) -> Option<String> {
+    let path = link.split(['?', '#']).next()?;
+    let name = path.rsplit('/').next()?;
+    if name.is_empty() {
+        return None;
+    }
+    percent_decode(name).or_else(|| Some(name.to_string()))
+}
+
+fn percent_decode(encoded: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut iter = encoded.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
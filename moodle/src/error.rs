@@ -0,0 +1,125 @@
+use serde::Deserialize;
+use thiserror::Error;
+
+/// Everything that can go wrong while talking to a moodle instance
+#[derive(Debug, Error)]
+pub enum MoodleError {
+    /// login/token.php refused to hand out a token, e.g. because of wrong credentials
+    #[error("login failed ({errorcode}): {message}")]
+    Login { errorcode: String, message: String },
+    /// The token does not exist (anymore) or has expired
+    #[error("the moodle token is invalid or has expired")]
+    InvalidToken,
+    /// The token is valid, but the service doesnt allow this function or the user lacks a capability
+    #[error("access denied ({errorcode}): {message}")]
+    AccessDenied { errorcode: String, message: String },
+    /// The requested activity is hidden, disabled or otherwise not accessible
+    #[error("module unavailable ({errorcode}): {message}")]
+    ModuleUnavailable { errorcode: String, message: String },
+    /// The site is in maintenance mode and doesnt answer web service calls
+    #[error("the moodle site is in maintenance mode")]
+    Maintenance,
+    /// Any other exception that moodle reported
+    #[error("moodle exception {exception} ({errorcode}): {message}")]
+    Exception {
+        exception: String,
+        errorcode: String,
+        message: String,
+    },
+    /// The request never got a proper answer
+    #[error("network error: {0}")]
+    Network(#[from] reqwest::Error),
+    /// The server (or a proxy in front of it) answered with an error status
+    #[error("moodle answered with status {0}")]
+    Status(reqwest::StatusCode),
+    /// All attempts together took longer than the retry policy allows
+    #[error("moodle didnt answer in time")]
+    Timeout,
+    /// The answer didnt match the expected format, body contains the raw response
+    #[error("failed to decode moodle response: {source}")]
+    Decode {
+        source: serde_json::Error,
+        body: String,
+    },
+    /// A download was aborted because the file is larger than allowed.
+    /// `size` is the announced length, or the bytes received until the limit was passed
+    #[error("file is too large ({size} bytes, at most {max_size} allowed)")]
+    TooLarge { size: u64, max_size: u64 },
+    /// Writing a download failed
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl MoodleError {
+    /// true if the account has to login again before any further request can succeed
+    pub fn is_auth_error(&self) -> bool {
+        matches!(self, MoodleError::InvalidToken)
+    }
+
+    /// true if repeating the same request might succeed. A connection that breaks while the
+    /// answer is read shows up as a body or decode error
+    pub fn is_transient(&self) -> bool {
+        match self {
+            MoodleError::Network(why) => {
+                why.is_timeout()
+                    || why.is_connect()
+                    || why.is_request()
+                    || why.is_body()
+                    || why.is_decode()
+            }
+            MoodleError::Status(status) => {
+                status.is_server_error() || *status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            MoodleError::Timeout => true,
+            _ => false,
+        }
+    }
+
+    /// true if the request certainly didnt reach moodle, so even a call that changes something
+    /// can be sent again
+    pub fn is_unsent(&self) -> bool {
+        matches!(self, MoodleError::Network(why) if why.is_connect())
+    }
+}
+
+/// The object moodle answers with (HTTP 200) when a web service call fails
+#[derive(Debug, Deserialize)]
+pub(crate) struct MoodleException {
+    pub exception: String,
+    pub errorcode: String,
+    pub message: String,
+}
+
+impl From<MoodleException> for MoodleError {
+    fn from(exception: MoodleException) -> Self {
+        let MoodleException {
+            exception,
+            errorcode,
+            message,
+        } = exception;
+        match errorcode.as_str() {
+            "invalidtoken" => MoodleError::InvalidToken,
+            "sitemaintenance" => MoodleError::Maintenance,
+            "accessexception" | "nopermissions" | "servicenotavailable" => {
+                MoodleError::AccessDenied { errorcode, message }
+            }
+            "requireloginerror"
+            | "activityiscurrentlyhidden"
+            | "invalidcoursemodule"
+            | "moduledisabled" => MoodleError::ModuleUnavailable { errorcode, message },
+            _ => MoodleError::Exception {
+                exception,
+                errorcode,
+                message,
+            },
+        }
+    }
+}
+
+/// Decode a web service answer, preferring moodles own exception over a generic serde error
+pub(crate) fn decode<T: serde::de::DeserializeOwned>(body: String) -> Result<T, MoodleError> {
+    if let Ok(exception) = serde_json::from_str::<MoodleException>(&body) {
+        return Err(exception.into());
+    }
+    serde_json::from_str(&body).map_err(|source| MoodleError::Decode { source, body })
+}
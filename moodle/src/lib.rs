@@ -1,71 +1,256 @@
+use crate::data::lenient::{self, LenientVec, ParseWarning};
 use crate::data::modules::assignment::Assignment;
 use crate::data::modules::bigbluebutton::Bigbluebuttonbn;
+use crate::data::modules::book::Book;
 use crate::data::modules::chat::Chat;
 use crate::data::modules::choice::Choice;
+use crate::data::modules::database::Database;
 use crate::data::modules::feedback::Feedback;
 use crate::data::modules::folder::Folder;
 use crate::data::modules::forum::Forum;
 use crate::data::modules::glossary::Glossary;
+use crate::data::modules::h5pactivity::H5pActivity;
 use crate::data::modules::label::Label;
+use crate::data::modules::lesson::Lesson;
 use crate::data::modules::page::Page;
 use crate::data::modules::quiz::Quiz;
 use crate::data::modules::resource::Resource;
 use crate::data::modules::url::Url;
+use crate::data::modules::wiki::{Wiki, WikiPage};
+use crate::data::modules::workshop::Workshop;
 use crate::data::modules::{
-    assignment, bigbluebutton, chat, choice, feedback, folder, forum, glossary, label, page, quiz,
-    resource, url,
+    assignment, bigbluebutton, book, chat, choice, database, feedback, folder, forum, glossary,
+    h5pactivity, label, lesson, page, quiz, resource, url, wiki, workshop,
 };
+use crate::data::multilang;
+use crate::data::other_content::calendar::{self, CalendarEvent};
+use crate::data::other_content::completion::{self, ActivityCompletion};
 use crate::data::other_content::courses::Data;
-use crate::data::other_content::section::Course;
-use anyhow::{anyhow, Result};
+use crate::data::other_content::discussion::{self, Discussion, ForumPost};
+use crate::data::other_content::grades::{self, CourseGrade, GradeItem};
+use crate::data::other_content::messages::{self, Conversation, ConversationMessage, Notification};
+use crate::data::other_content::section::{Course, Section};
+use crate::data::other_content::site_info::SiteInfo;
+use crate::data::other_content::submission_status::{self, SubmissionStatus};
+use crate::error::decode;
+use crate::retry::RetryPolicy;
+use reqwest::{Client, RequestBuilder};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use reqwest::Client;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::{OnceCell, Semaphore, SemaphorePermit};
 
 pub mod data;
+pub mod download;
+pub mod error;
+pub mod retry;
 
+pub use error::MoodleError;
+
+/// How many requests a client sends at the same time when scanning a course
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Shortname of the service the official moodle app uses, it is enabled on most sites
+pub const MOBILE_SERVICE: &str = "moodle_mobile_app";
+
+/// answer of login/token.php, which doesnt use the web service exception format
 #[derive(Debug, Deserialize)]
-struct Token {
-    pub token: String,
+#[serde(untagged)]
+enum TokenResponse {
+    Token {
+        token: String,
+        privatetoken: Option<String>,
+    },
+    Error {
+        error: String,
+        errorcode: String,
+    },
+}
+
+/// The outcome of a successful password login
+#[derive(Debug, Clone)]
+pub struct Login {
+    pub client: Moodle,
+    /// only sent over https, can be used to create autologin keys for the website
+    pub private_token: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Moodle {
     base: String,
     token: String,
+    #[serde(skip)]
+    retry: RetryPolicy,
+    #[serde(skip, default = "shared_client")]
+    http: Client,
+    #[serde(skip, default = "default_concurrency")]
+    concurrency: usize,
+    // one permit per request that may be running, shared between clones
+    #[serde(skip, default = "default_limit")]
+    limit: Arc<Semaphore>,
+    // shared between clones, so a cloned client doesnt ask again
+    #[serde(skip)]
+    site_info: Arc<OnceCell<SiteInfo>>,
+    #[serde(skip)]
+    warnings: Option<UnboundedSender<ParseWarning>>,
+    #[serde(skip)]
+    language: Option<String>,
+    #[serde(skip)]
+    filter: bool,
+}
+
+fn default_concurrency() -> usize {
+    DEFAULT_CONCURRENCY
+}
+
+fn default_limit() -> Arc<Semaphore> {
+    Arc::new(Semaphore::new(DEFAULT_CONCURRENCY))
+}
+
+/// One connection pool for all clients, so that accounts on the same site reuse connections
+fn shared_client() -> Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT
+        .get_or_init(|| {
+            Client::builder()
+                .user_agent(concat!(
+                    env!("CARGO_PKG_NAME"),
+                    "/",
+                    env!("CARGO_PKG_VERSION")
+                ))
+                .gzip(true)
+                .tcp_keepalive(Duration::from_secs(60))
+                .pool_idle_timeout(Duration::from_secs(90))
+                .build()
+                .expect("Failed to initialize the http client")
+        })
+        .clone()
 }
 
 impl Moodle {
     pub fn new_with_token(base: String, token: String) -> Moodle {
-        Moodle { base, token }
+        Moodle {
+            base,
+            token,
+            retry: RetryPolicy::default(),
+            http: shared_client(),
+            concurrency: DEFAULT_CONCURRENCY,
+            limit: default_limit(),
+            site_info: Arc::new(OnceCell::new()),
+            warnings: None,
+            language: None,
+            filter: false,
+        }
     }
 
-    pub async fn new_with_login(base: String, username: &str, password: &str) -> Result<Moodle> {
-        let url = format!(
-            "{base}/login/token.php?username={username}&password={password}&service=MOODLE_MOBILE_APP"
-        );
-        let response = reqwest::get(url).await?;
-        let token = response.json::<Token>().await?.token;
-        Ok(Moodle { base, token })
+    /// limit how many requests this client and its clones send at the same time
+    pub fn with_concurrency(mut self, limit: usize) -> Moodle {
+        self.concurrency = limit.max(1);
+        self.limit = Arc::new(Semaphore::new(self.concurrency));
+        self
     }
 
-    pub async fn download_file(&self, link: String, max_size: u64) -> Result<Vec<u8>> {
-        let url = if link.contains("?") {
-            format!("{}&token={}", link, self.token)
-        } else {
-            format!("{}?token={}", link, self.token)
-        };
-        let response = reqwest::get(url).await?;
-        if let Some(length) = response.content_length() {
-            if length > max_size {
-                return Err(anyhow!("Fail"));
-            }
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// use a custom http client instead of the shared one, e.g. for a proxy
+    pub fn with_http_client(mut self, client: Client) -> Moodle {
+        self.http = client;
+        self
+    }
+
+    /// replace the default retry policy that is used for all requests of this client
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Moodle {
+        self.retry = policy;
+        self
+    }
+
+    /// report list elements that couldnt be parsed and were skipped, without a sender they are dropped
+    pub fn with_warnings(mut self, sender: UnboundedSender<ParseWarning>) -> Moodle {
+        self.warnings = Some(sender);
+        self
+    }
+
+    /// show multilang content in `language` (e.g. "en", "de_du"). The multilang blocks are
+    /// resolved locally, unless moodle already filtered them, see [Moodle::with_filter]
+    pub fn with_language(mut self, language: &str) -> Moodle {
+        self.language = Some(language.to_string());
+        self
+    }
+
+    /// let moodle run its text filters on every answer. Besides multilang these can be
+    /// auto-linking, emoticons or MathJax, so switching it on changes how stored texts look.
+    /// Off by default
+    pub fn with_filter(mut self, filter: bool) -> Moodle {
+        self.filter = filter;
+        self
+    }
+
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// login with the moodle app service
+    pub async fn new_with_login(
+        base: String,
+        username: &str,
+        password: &str,
+    ) -> Result<Moodle, MoodleError> {
+        let login = Moodle::login(base, username, password, MOBILE_SERVICE).await?;
+        Ok(login.client)
+    }
+
+    /// request a token for `service`. The credentials are posted as a form so they neither need
+    /// escaping by the caller nor end up in access logs
+    pub async fn login(
+        base: String,
+        username: &str,
+        password: &str,
+        service: &str,
+    ) -> Result<Login, MoodleError> {
+        let params = ParameterBuilder::new()
+            .add("username", username)
+            .add("password", password)
+            .add("service", service);
+
+        let retry = RetryPolicy::default();
+        let http = shared_client();
+        let url = format!("{base}/login/token.php");
+        // asking for the token again just returns the same token
+        let body = send_text(&retry, "login/token.php", true, || {
+            http.post(&url).form(&params.map)
+        })
+        .await?;
+
+        match decode::<TokenResponse>(body)? {
+            TokenResponse::Token {
+                token,
+                privatetoken,
+            } => Ok(Login {
+                client: Moodle::new_with_token(base, token),
+                private_token: privatetoken,
+            }),
+            TokenResponse::Error { error, errorcode } => Err(MoodleError::Login {
+                errorcode,
+                message: error,
+            }),
         }
-        Ok(response.bytes().await?.to_vec())
     }
 
-    pub async fn get_courses(&self, classification: Option<&str>) -> Result<Data> {
+    /// information about the site and the token's user, only requested once per client
+    pub async fn site_info(&self) -> Result<&SiteInfo, MoodleError> {
+        self.site_info
+            .get_or_try_init(|| {
+                self.request("core_webservice_get_site_info", ParameterBuilder::new())
+            })
+            .await
+    }
+
+    pub async fn get_courses(&self, classification: Option<&str>) -> Result<Data, MoodleError> {
         let params = ParameterBuilder::new().add("classification", classification.unwrap_or("all"));
         self.request(
             "core_course_get_enrolled_courses_by_timeline_classification",
@@ -74,134 +259,785 @@ impl Moodle {
         .await
     }
 
-    pub async fn get_course_contents(&self, course_id: i64) -> Result<Course> {
+    pub async fn get_course_contents(&self, course_id: i64) -> Result<Course, MoodleError> {
         let params = ParameterBuilder::new().add("courseid", course_id.to_string().as_str());
-        self.request("core_course_get_contents", params).await
+        let res: LenientVec<Section> = self.request("core_course_get_contents", params).await?;
+        Ok(res.0)
+    }
+
+    /// action events (deadlines the user still has to act on) of all courses, sorted by time.
+    /// moodle caps `limit` at 50
+    pub async fn get_action_events_by_timesort(
+        &self,
+        from: i64,
+        to: Option<i64>,
+        limit: u32,
+    ) -> Result<Vec<CalendarEvent>, MoodleError> {
+        let mut params = ParameterBuilder::new()
+            .add("timesortfrom", &from.to_string())
+            .add("limitnum", &limit.to_string());
+        if let Some(to) = to {
+            params = params.add("timesortto", &to.to_string());
+        }
+        let res: calendar::ActionEvents = self
+            .request("core_calendar_get_action_events_by_timesort", params)
+            .await?;
+        Ok(res.events.into_iter().map(CalendarEvent::from).collect())
+    }
+
+    /// action events of a single course, sorted by time
+    pub async fn get_action_events_by_course(
+        &self,
+        course_id: i64,
+        from: i64,
+        to: Option<i64>,
+        limit: u32,
+    ) -> Result<Vec<CalendarEvent>, MoodleError> {
+        let mut params = ParameterBuilder::new()
+            .add("courseid", &course_id.to_string())
+            .add("timesortfrom", &from.to_string())
+            .add("limitnum", &limit.to_string());
+        if let Some(to) = to {
+            params = params.add("timesortto", &to.to_string());
+        }
+        let res: calendar::ActionEvents = self
+            .request("core_calendar_get_action_events_by_course", params)
+            .await?;
+        Ok(res.events.into_iter().map(CalendarEvent::from).collect())
+    }
+
+    /// the final grade of every course of the user
+    pub async fn get_course_grades(&self) -> Result<Vec<CourseGrade>, MoodleError> {
+        let res: grades::CourseGrades = self
+            .request(
+                "gradereport_overview_get_course_grades",
+                ParameterBuilder::new(),
+            )
+            .await?;
+        Ok(res.grades.into_iter().map(CourseGrade::from).collect())
+    }
+
+    /// the grade report of the token's user for a course, the course total comes last
+    pub async fn get_grade_items(&self, course_id: i64) -> Result<Vec<GradeItem>, MoodleError> {
+        let user_id = self.site_info().await?.userid;
+        let params = ParameterBuilder::new()
+            .add("courseid", &course_id.to_string())
+            .add("userid", &user_id.to_string());
+        let res: grades::UserGrades = self
+            .request("gradereport_user_get_grade_items", params)
+            .await?;
+        Ok(res
+            .usergrades
+            .into_iter()
+            .flat_map(|user| user.gradeitems)
+            .map(GradeItem::from)
+            .collect())
+    }
+
+    /// completion state of every activity of a course that tracks completion, for the token's user
+    pub async fn get_activities_completion(
+        &self,
+        course_id: i64,
+    ) -> Result<Vec<ActivityCompletion>, MoodleError> {
+        let user_id = self.site_info().await?.userid;
+        let params = ParameterBuilder::new()
+            .add("courseid", &course_id.to_string())
+            .add("userid", &user_id.to_string());
+        let res: completion::Statuses = self
+            .request("core_completion_get_activities_completion_status", params)
+            .await?;
+        Ok(res
+            .statuses
+            .into_iter()
+            .map(ActivityCompletion::from)
+            .collect())
     }
 
-    pub async fn get_assignments_for_course(&self, course_id: i64) -> Result<Vec<Assignment>> {
-        let mut res: assignment::Root = self
-            .module_request("mod_assign_get_assignments", course_id)
+    /// mark a manually tracked activity as done or not done
+    pub async fn update_completion_manually(
+        &self,
+        module_id: i64,
+        completed: bool,
+    ) -> Result<(), MoodleError> {
+        let params = ParameterBuilder::new()
+            .add("cmid", &module_id.to_string())
+            .add("completed", if completed { "1" } else { "0" });
+        let res: completion::UpdateStatus = self
+            .mutate(
+                "core_completion_update_activity_completion_status_manually",
+                params,
+            )
             .await?;
-        match res.courses.pop() {
-            Some(course) => Ok(course.assignments),
-            None => Err(anyhow!("Didnt receive course as answer")),
+        if res.status {
+            Ok(())
+        } else {
+            Err(MoodleError::Exception {
+                exception: "core_completion_update_activity_completion_status_manually".into(),
+                errorcode: "completionnotupdated".into(),
+                message: "moodle didnt change the completion state".into(),
+            })
         }
     }
 
-    pub async fn get_bbbs_for_course(&self, course_id: i64) -> Result<Vec<Bigbluebuttonbn>> {
+    /// the latest popup notifications of the token's user, newest first
+    pub async fn get_popup_notifications(
+        &self,
+        limit: u32,
+    ) -> Result<Vec<Notification>, MoodleError> {
+        let user_id = self.site_info().await?.userid;
+        let params = ParameterBuilder::new()
+            .add("useridto", &user_id.to_string())
+            .add("newestfirst", "1")
+            .add("limit", &limit.to_string())
+            .add("offset", "0");
+        let res: messages::Notifications = self
+            .request("message_popup_get_popup_notifications", params)
+            .await?;
+        Ok(res
+            .notifications
+            .into_iter()
+            .map(Notification::from)
+            .collect())
+    }
+
+    /// the conversations of the token's user, the ones with the newest messages first
+    pub async fn get_conversations(&self, limit: u32) -> Result<Vec<Conversation>, MoodleError> {
+        let user_id = self.site_info().await?.userid;
+        let params = ParameterBuilder::new()
+            .add("userid", &user_id.to_string())
+            .add("limitfrom", "0")
+            .add("limitnum", &limit.to_string());
+        let res: messages::Conversations = self
+            .request("core_message_get_conversations", params)
+            .await?;
+        Ok(res
+            .conversations
+            .into_iter()
+            .map(Conversation::from)
+            .collect())
+    }
+
+    /// the latest messages of a conversation, newest first
+    pub async fn get_conversation_messages(
+        &self,
+        conversation_id: i64,
+        limit: u32,
+    ) -> Result<Vec<ConversationMessage>, MoodleError> {
+        let user_id = self.site_info().await?.userid;
+        let params = ParameterBuilder::new()
+            .add("currentuserid", &user_id.to_string())
+            .add("convid", &conversation_id.to_string())
+            .add("limitfrom", "0")
+            .add("limitnum", &limit.to_string())
+            .add("newest", "1");
+        let res: messages::Messages = self
+            .request("core_message_get_conversation_messages", params)
+            .await?;
+        Ok(res.into_messages())
+    }
+
+    /// send a private message to a user, returns the id of the new message
+    pub async fn send_instant_message(
+        &self,
+        to_user_id: i64,
+        text: &str,
+    ) -> Result<i64, MoodleError> {
+        let params = ParameterBuilder::new()
+            .add("messages[0][touserid]", &to_user_id.to_string())
+            .add("messages[0][text]", text)
+            .add("messages[0][textformat]", "2"); // plain text
+        let res: LenientVec<messages::SentMessage> = self
+            .mutate("core_message_send_instant_messages", params)
+            .await?;
+        match res.0.into_iter().next() {
+            Some(sent) if sent.msgid > 0 => Ok(sent.msgid),
+            // moodle reports refused messages (e.g. blocked users) inside a normal answer
+            sent => Err(MoodleError::Exception {
+                exception: "core_message_send_instant_messages".into(),
+                errorcode: "messagenotsent".into(),
+                message: sent.and_then(|sent| sent.errormessage).unwrap_or_default(),
+            }),
+        }
+    }
+
+    /// all calendar events of the courses between `from` and `to`, including user and site events
+    pub async fn get_calendar_events(
+        &self,
+        course_ids: &[i64],
+        from: i64,
+        to: Option<i64>,
+    ) -> Result<Vec<CalendarEvent>, MoodleError> {
+        let mut params = course_ids
+            .iter()
+            .enumerate()
+            .fold(ParameterBuilder::new(), |params, (i, id)| {
+                params.add(&format!("events[courseids][{i}]"), &id.to_string())
+            })
+            .add("options[userevents]", "1")
+            .add("options[siteevents]", "1")
+            .add("options[timestart]", &from.to_string());
+        if let Some(to) = to {
+            params = params.add("options[timeend]", &to.to_string());
+        }
+        let res: calendar::Events = self
+            .request("core_calendar_get_calendar_events", params)
+            .await?;
+        Ok(res.events.into_iter().map(CalendarEvent::from).collect())
+    }
+
+    pub async fn get_assignments_for_course(
+        &self,
+        course_id: i64,
+    ) -> Result<Vec<Assignment>, MoodleError> {
+        let mut res = self.get_assignments_for_courses(&[course_id]).await?;
+        Ok(res.remove(&course_id).unwrap_or_default())
+    }
+
+    pub async fn get_assignments_for_courses(
+        &self,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Assignment>>, MoodleError> {
+        let res: assignment::Root = self
+            .module_request("mod_assign_get_assignments", course_ids)
+            .await?;
+        let assignments = res
+            .courses
+            .into_iter()
+            .flat_map(|course| course.assignments)
+            .collect();
+        Ok(group_by_course(course_ids, assignments, |assignment| {
+            assignment.course
+        }))
+    }
+
+    /// submission and grading state of an assignment for the token's user
+    pub async fn get_submission_status(
+        &self,
+        assign_id: i64,
+    ) -> Result<SubmissionStatus, MoodleError> {
+        let params = ParameterBuilder::new().add("assignid", &assign_id.to_string());
+        let res: submission_status::StatusRoot = self
+            .request("mod_assign_get_submission_status", params)
+            .await?;
+        Ok(res.into())
+    }
+
+    pub async fn get_bbbs_for_course(
+        &self,
+        course_id: i64,
+    ) -> Result<Vec<Bigbluebuttonbn>, MoodleError> {
+        let mut res = self.get_bbbs_for_courses(&[course_id]).await?;
+        Ok(res.remove(&course_id).unwrap_or_default())
+    }
+
+    pub async fn get_bbbs_for_courses(
+        &self,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Bigbluebuttonbn>>, MoodleError> {
         let res: bigbluebutton::Root = self
             .module_request(
                 "mod_bigbluebuttonbn_get_bigbluebuttonbns_by_courses",
-                course_id,
+                course_ids,
             )
             .await?;
-        Ok(res.bigbluebuttonbns)
+        Ok(group_by_course(
+            course_ids,
+            res.bigbluebuttonbns,
+            |module| module.course,
+        ))
+    }
+
+    pub async fn get_books_for_course(&self, course_id: i64) -> Result<Vec<Book>, MoodleError> {
+        let mut res = self.get_books_for_courses(&[course_id]).await?;
+        Ok(res.remove(&course_id).unwrap_or_default())
+    }
+
+    pub async fn get_books_for_courses(
+        &self,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Book>>, MoodleError> {
+        let res: book::Root = self
+            .module_request("mod_book_get_books_by_courses", course_ids)
+            .await?;
+        Ok(group_by_course(course_ids, res.books, |module| {
+            module.course
+        }))
     }
 
-    pub async fn get_chats_for_course(&self, course_id: i64) -> Result<Vec<Chat>> {
+    pub async fn get_chats_for_course(&self, course_id: i64) -> Result<Vec<Chat>, MoodleError> {
+        let mut res = self.get_chats_for_courses(&[course_id]).await?;
+        Ok(res.remove(&course_id).unwrap_or_default())
+    }
+
+    pub async fn get_chats_for_courses(
+        &self,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Chat>>, MoodleError> {
         let res: chat::Root = self
-            .module_request("mod_chat_get_chats_by_courses", course_id)
+            .module_request("mod_chat_get_chats_by_courses", course_ids)
             .await?;
-        Ok(res.chats)
+        Ok(group_by_course(course_ids, res.chats, |module| {
+            module.course
+        }))
+    }
+
+    pub async fn get_choices_for_course(&self, course_id: i64) -> Result<Vec<Choice>, MoodleError> {
+        let mut res = self.get_choices_for_courses(&[course_id]).await?;
+        Ok(res.remove(&course_id).unwrap_or_default())
     }
 
-    pub async fn get_choices_for_course(&self, course_id: i64) -> Result<Vec<Choice>> {
+    pub async fn get_choices_for_courses(
+        &self,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Choice>>, MoodleError> {
         let res: choice::Root = self
-            .module_request("mod_choice_get_choices_by_courses", course_id)
+            .module_request("mod_choice_get_choices_by_courses", course_ids)
             .await?;
-        Ok(res.choices)
+        Ok(group_by_course(course_ids, res.choices, |module| {
+            module.course
+        }))
+    }
+
+    pub async fn get_databases_for_course(
+        &self,
+        course_id: i64,
+    ) -> Result<Vec<Database>, MoodleError> {
+        let mut res = self.get_databases_for_courses(&[course_id]).await?;
+        Ok(res.remove(&course_id).unwrap_or_default())
     }
 
-    pub async fn get_feedback_for_course(&self, course_id: i64) -> Result<Vec<Feedback>> {
+    pub async fn get_databases_for_courses(
+        &self,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Database>>, MoodleError> {
+        let res: database::Root = self
+            .module_request("mod_data_get_databases_by_courses", course_ids)
+            .await?;
+        Ok(group_by_course(course_ids, res.databases, |module| {
+            module.course
+        }))
+    }
+
+    /// number of entries of a database activity that the token's user can see
+    pub async fn get_database_entry_count(&self, database_id: i64) -> Result<i64, MoodleError> {
+        let params = ParameterBuilder::new()
+            .add("databaseid", &database_id.to_string())
+            .add("returncontents", "0")
+            .add("page", "0")
+            .add("perpage", "1");
+        let res: database::EntriesRoot = self.request("mod_data_get_entries", params).await?;
+        Ok(res.totalcount)
+    }
+
+    pub async fn get_feedback_for_course(
+        &self,
+        course_id: i64,
+    ) -> Result<Vec<Feedback>, MoodleError> {
+        let mut res = self.get_feedback_for_courses(&[course_id]).await?;
+        Ok(res.remove(&course_id).unwrap_or_default())
+    }
+
+    pub async fn get_feedback_for_courses(
+        &self,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Feedback>>, MoodleError> {
         let res: feedback::Root = self
-            .module_request("mod_feedback_get_feedbacks_by_courses", course_id)
+            .module_request("mod_feedback_get_feedbacks_by_courses", course_ids)
             .await?;
-        Ok(res.feedbacks)
+        Ok(group_by_course(course_ids, res.feedbacks, |module| {
+            module.course
+        }))
+    }
+
+    pub async fn get_folders_for_course(&self, course_id: i64) -> Result<Vec<Folder>, MoodleError> {
+        let mut res = self.get_folders_for_courses(&[course_id]).await?;
+        Ok(res.remove(&course_id).unwrap_or_default())
     }
 
-    pub async fn get_folders_for_course(&self, course_id: i64) -> Result<Vec<Folder>> {
+    pub async fn get_folders_for_courses(
+        &self,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Folder>>, MoodleError> {
         let res: folder::Root = self
-            .module_request("mod_folder_get_folders_by_courses", course_id)
+            .module_request("mod_folder_get_folders_by_courses", course_ids)
             .await?;
-        Ok(res.folders)
+        Ok(group_by_course(course_ids, res.folders, |module| {
+            module.course
+        }))
+    }
+
+    pub async fn get_forums_for_course(&self, course_id: i64) -> Result<Vec<Forum>, MoodleError> {
+        let mut res = self.get_forums_for_courses(&[course_id]).await?;
+        Ok(res.remove(&course_id).unwrap_or_default())
     }
 
-    pub async fn get_forums_for_course(&self, course_id: i64) -> Result<Vec<Forum>> {
+    pub async fn get_forums_for_courses(
+        &self,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Forum>>, MoodleError> {
         let res: forum::Root = self
-            .module_request("mod_forum_get_forums_by_courses", course_id)
+            .module_request("mod_forum_get_forums_by_courses", course_ids)
             .await?;
-        Ok(res)
+        Ok(group_by_course(course_ids, res.0, |module| module.course))
     }
 
-    pub async fn get_glossaries_for_course(&self, course_id: i64) -> Result<Vec<Glossary>> {
+    /// the latest discussions of a forum, the ones with the newest posts first
+    pub async fn get_forum_discussions(
+        &self,
+        forum_id: i64,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Vec<Discussion>, MoodleError> {
+        let params = ParameterBuilder::new()
+            .add("forumid", &forum_id.to_string())
+            .add("page", &page.to_string())
+            .add("perpage", &per_page.to_string());
+        let res: discussion::Discussions = self
+            .request("mod_forum_get_forum_discussions", params)
+            .await?;
+        Ok(res
+            .discussions
+            .into_iter()
+            .map(|raw| raw.into_discussion(&self.base, forum_id))
+            .collect())
+    }
+
+    /// every post of a discussion, oldest first
+    pub async fn get_discussion_posts(
+        &self,
+        discussion_id: i64,
+    ) -> Result<Vec<ForumPost>, MoodleError> {
+        let params = ParameterBuilder::new()
+            .add("discussionid", &discussion_id.to_string())
+            .add("sortby", "created")
+            .add("sortdirection", "ASC");
+        let res: discussion::Posts = self
+            .request("mod_forum_get_discussion_posts", params)
+            .await?;
+        Ok(res.posts.into_iter().map(ForumPost::from).collect())
+    }
+
+    pub async fn get_glossaries_for_course(
+        &self,
+        course_id: i64,
+    ) -> Result<Vec<Glossary>, MoodleError> {
+        let mut res = self.get_glossaries_for_courses(&[course_id]).await?;
+        Ok(res.remove(&course_id).unwrap_or_default())
+    }
+
+    pub async fn get_glossaries_for_courses(
+        &self,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Glossary>>, MoodleError> {
         let res: glossary::Root = self
-            .module_request("mod_glossary_get_glossaries_by_courses", course_id)
+            .module_request("mod_glossary_get_glossaries_by_courses", course_ids)
+            .await?;
+        Ok(group_by_course(course_ids, res.glossaries, |module| {
+            module.course
+        }))
+    }
+
+    pub async fn get_h5pactivities_for_course(
+        &self,
+        course_id: i64,
+    ) -> Result<Vec<H5pActivity>, MoodleError> {
+        let mut res = self.get_h5pactivities_for_courses(&[course_id]).await?;
+        Ok(res.remove(&course_id).unwrap_or_default())
+    }
+
+    pub async fn get_h5pactivities_for_courses(
+        &self,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<H5pActivity>>, MoodleError> {
+        let res: h5pactivity::Root = self
+            .module_request("mod_h5pactivity_get_h5pactivities_by_courses", course_ids)
             .await?;
-        Ok(res.glossaries)
+        Ok(group_by_course(course_ids, res.h5pactivities, |module| {
+            module.course
+        }))
+    }
+
+    pub async fn get_labels_for_course(&self, course_id: i64) -> Result<Vec<Label>, MoodleError> {
+        let mut res = self.get_labels_for_courses(&[course_id]).await?;
+        Ok(res.remove(&course_id).unwrap_or_default())
     }
 
-    pub async fn get_labels_for_course(&self, course_id: i64) -> Result<Vec<Label>> {
+    pub async fn get_labels_for_courses(
+        &self,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Label>>, MoodleError> {
         let res: label::Root = self
-            .module_request("mod_label_get_labels_by_courses", course_id)
+            .module_request("mod_label_get_labels_by_courses", course_ids)
+            .await?;
+        Ok(group_by_course(course_ids, res.labels, |module| {
+            module.course
+        }))
+    }
+
+    pub async fn get_lessons_for_course(&self, course_id: i64) -> Result<Vec<Lesson>, MoodleError> {
+        let mut res = self.get_lessons_for_courses(&[course_id]).await?;
+        Ok(res.remove(&course_id).unwrap_or_default())
+    }
+
+    pub async fn get_lessons_for_courses(
+        &self,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Lesson>>, MoodleError> {
+        let res: lesson::Root = self
+            .module_request("mod_lesson_get_lessons_by_courses", course_ids)
             .await?;
-        Ok(res.labels)
+        Ok(group_by_course(course_ids, res.lessons, |module| {
+            module.course
+        }))
     }
 
-    pub async fn get_pages_for_course(&self, course_id: i64) -> Result<Vec<Page>> {
+    pub async fn get_pages_for_course(&self, course_id: i64) -> Result<Vec<Page>, MoodleError> {
+        let mut res = self.get_pages_for_courses(&[course_id]).await?;
+        Ok(res.remove(&course_id).unwrap_or_default())
+    }
+
+    pub async fn get_pages_for_courses(
+        &self,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Page>>, MoodleError> {
         let res: page::Root = self
-            .module_request("mod_page_get_pages_by_courses", course_id)
+            .module_request("mod_page_get_pages_by_courses", course_ids)
             .await?;
-        Ok(res.pages)
+        Ok(group_by_course(course_ids, res.pages, |module| {
+            module.course
+        }))
     }
 
-    pub async fn get_quizzes_for_course(&self, course_id: i64) -> Result<Vec<Quiz>> {
+    pub async fn get_quizzes_for_course(&self, course_id: i64) -> Result<Vec<Quiz>, MoodleError> {
+        let mut res = self.get_quizzes_for_courses(&[course_id]).await?;
+        Ok(res.remove(&course_id).unwrap_or_default())
+    }
+
+    pub async fn get_quizzes_for_courses(
+        &self,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Quiz>>, MoodleError> {
         let res: quiz::Root = self
-            .module_request("mod_quiz_get_quizzes_by_courses", course_id)
+            .module_request("mod_quiz_get_quizzes_by_courses", course_ids)
             .await?;
-        Ok(res.quizzes)
+        Ok(group_by_course(course_ids, res.quizzes, |module| {
+            module.course
+        }))
+    }
+
+    pub async fn get_resources_for_course(
+        &self,
+        course_id: i64,
+    ) -> Result<Vec<Resource>, MoodleError> {
+        let mut res = self.get_resources_for_courses(&[course_id]).await?;
+        Ok(res.remove(&course_id).unwrap_or_default())
     }
 
-    pub async fn get_resources_for_course(&self, course_id: i64) -> Result<Vec<Resource>> {
+    pub async fn get_resources_for_courses(
+        &self,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Resource>>, MoodleError> {
         let res: resource::Root = self
-            .module_request("mod_resource_get_resources_by_courses", course_id)
+            .module_request("mod_resource_get_resources_by_courses", course_ids)
             .await?;
-        Ok(res.resources)
+        Ok(group_by_course(course_ids, res.resources, |module| {
+            module.course
+        }))
     }
 
-    pub async fn get_urls_for_course(&self, course_id: i64) -> Result<Vec<Url>> {
+    pub async fn get_urls_for_course(&self, course_id: i64) -> Result<Vec<Url>, MoodleError> {
+        let mut res = self.get_urls_for_courses(&[course_id]).await?;
+        Ok(res.remove(&course_id).unwrap_or_default())
+    }
+
+    pub async fn get_urls_for_courses(
+        &self,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Url>>, MoodleError> {
         let res: url::Root = self
-            .module_request("mod_url_get_urls_by_courses", course_id)
+            .module_request("mod_url_get_urls_by_courses", course_ids)
             .await?;
-        Ok(res.urls)
+        Ok(group_by_course(course_ids, res.urls, |module| {
+            module.course
+        }))
     }
 
-    async fn module_request<T: DeserializeOwned>(
+    pub async fn get_wikis_for_course(&self, course_id: i64) -> Result<Vec<Wiki>, MoodleError> {
+        let mut res = self.get_wikis_for_courses(&[course_id]).await?;
+        Ok(res.remove(&course_id).unwrap_or_default())
+    }
+
+    pub async fn get_wikis_for_courses(
+        &self,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Wiki>>, MoodleError> {
+        let res: wiki::Root = self
+            .module_request("mod_wiki_get_wikis_by_courses", course_ids)
+            .await?;
+        Ok(group_by_course(course_ids, res.wikis, |module| {
+            module.course
+        }))
+    }
+
+    /// the pages of the wiki of the token's user (collaborative wikis only have one)
+    pub async fn get_wiki_pages(&self, wiki_id: i64) -> Result<Vec<WikiPage>, MoodleError> {
+        let params = ParameterBuilder::new().add("wikiid", &wiki_id.to_string());
+        let res: wiki::PagesRoot = self.request("mod_wiki_get_subwiki_pages", params).await?;
+        Ok(res.pages)
+    }
+
+    pub async fn get_workshops_for_course(
         &self,
-        function: &str,
         course_id: i64,
-    ) -> Result<T> {
-        let params = ParameterBuilder::new().add("courseids[0]", &course_id.to_string());
+    ) -> Result<Vec<Workshop>, MoodleError> {
+        let mut res = self.get_workshops_for_courses(&[course_id]).await?;
+        Ok(res.remove(&course_id).unwrap_or_default())
+    }
+
+    pub async fn get_workshops_for_courses(
+        &self,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Workshop>>, MoodleError> {
+        let res: workshop::Root = self
+            .module_request("mod_workshop_get_workshops_by_courses", course_ids)
+            .await?;
+        Ok(group_by_course(course_ids, res.workshops, |module| {
+            module.course
+        }))
+    }
+
+    /// all mod_*_get_*_by_courses functions accept a list of courses, also usable for the
+    /// functions of plugins that this crate doesnt know
+    pub async fn module_request<T: DeserializeOwned>(
+        &self,
+        function: &str,
+        course_ids: &[i64],
+    ) -> Result<T, MoodleError> {
+        let params = course_ids
+            .iter()
+            .enumerate()
+            .fold(ParameterBuilder::new(), |params, (i, id)| {
+                params.add(&format!("courseids[{i}]"), &id.to_string())
+            });
         self.request(function, params).await
     }
 
+    /// call a web service function that only reads, moodle exceptions are turned into typed errors
     async fn request<T: DeserializeOwned>(
         &self,
         function: &str,
         params: ParameterBuilder,
-    ) -> Result<T> {
-        let params = params
+    ) -> Result<T, MoodleError> {
+        self.call(function, params, true).await
+    }
+
+    /// call a web service function that changes something, e.g. sends a message. It is only sent
+    /// again if the first attempt never reached moodle, so it cant be processed twice
+    async fn mutate<T: DeserializeOwned>(
+        &self,
+        function: &str,
+        params: ParameterBuilder,
+    ) -> Result<T, MoodleError> {
+        self.call(function, params, false).await
+    }
+
+    async fn call<T: DeserializeOwned>(
+        &self,
+        function: &str,
+        params: ParameterBuilder,
+        idempotent: bool,
+    ) -> Result<T, MoodleError> {
+        let mut params = params
             .add("moodlewsrestformat", "json")
             .add("wsfunction", function)
             .add("wstoken", &self.token);
+        if self.filter {
+            params = params.add("moodlewssettingfilter", "true");
+        }
+        if let Some(language) = &self.language {
+            params = params.add("moodlewssettinglang", language);
+        }
+
+        let url = format!("{}/webservice/rest/server.php", self.base);
+        let permit = self.permit().await;
+        let body = send_text(&self.retry, function, idempotent, || {
+            self.http.post(&url).form(&params.map)
+        })
+        .await?;
+        drop(permit);
+        let body = match &self.language {
+            Some(language) => multilang::resolve_body(body, language),
+            None => body,
+        };
 
-        let response = Client::new()
-            .post(format!("{}/webservice/rest/server.php", self.base))
-            .form(&params.map)
-            .send().await?;
+        let (res, warnings) = lenient::collect(function, || decode(body));
+        for warning in warnings {
+            self.warn(warning);
+        }
+        res
+    }
+
+    /// wait until less than `concurrency` requests of this client are running.
+    /// A permit only covers a single request, so nested streams of requests cant block each other
+    pub(crate) async fn permit(&self) -> SemaphorePermit<'_> {
+        self.limit
+            .acquire()
+            .await
+            .expect("the semaphore of a client is never closed")
+    }
+
+    /// hand a warning to the sender of [Moodle::with_warnings], if there is one
+    pub(crate) fn warn(&self, warning: ParseWarning) {
+        if let Some(sender) = &self.warnings {
+            // the receiver might be gone already, the warnings are optional anyway
+            let _ = sender.send(warning);
+        }
+    }
+}
+
+/// sort modules into their courses, every requested course gets an entry even if it has no modules
+pub fn group_by_course<T>(
+    course_ids: &[i64],
+    modules: Vec<T>,
+    course: impl Fn(&T) -> i64,
+) -> HashMap<i64, Vec<T>> {
+    let mut grouped: HashMap<i64, Vec<T>> = course_ids.iter().map(|id| (*id, vec![])).collect();
+    for module in modules {
+        grouped.entry(course(&module)).or_default().push(module);
+    }
+    grouped
+}
 
-        Ok(response.json::<T>().await?)
+/// send a single attempt, error statuses count as failure
+async fn send(
+    policy: &RetryPolicy,
+    request: RequestBuilder,
+) -> Result<reqwest::Response, MoodleError> {
+    let request = match policy.get_request_timeout() {
+        Some(timeout) => request.timeout(timeout),
+        None => request,
+    };
+    let response = request.send().await?;
+    let status = response.status();
+    if status.is_client_error() || status.is_server_error() {
+        return Err(MoodleError::Status(status));
     }
+    Ok(response)
+}
+
+/// send a request with retries and read the whole answer as text
+async fn send_text(
+    policy: &RetryPolicy,
+    target: &str,
+    idempotent: bool,
+    request: impl Fn() -> RequestBuilder,
+) -> Result<String, MoodleError> {
+    policy
+        .run(target, idempotent, || async {
+            let response = send(policy, request()).await?;
+            Ok(response.text().await?)
+        })
+        .await
 }
 
 struct ParameterBuilder {
@@ -0,0 +1,171 @@
+use crate::MoodleError;
+use rand::Rng;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Information about a single try of a request, handed to [RetryPolicy::on_attempt]
+#[derive(Debug)]
+pub struct Attempt<'a> {
+    /// the web service function or the file that was requested
+    pub target: &'a str,
+    /// starts at 1
+    pub number: u32,
+    /// None if the attempt succeeded
+    pub error: Option<&'a MoodleError>,
+    /// how long the client waits before the next attempt, None if it gives up (or succeeded)
+    pub retry_in: Option<Duration>,
+}
+
+type AttemptHook = Arc<dyn Fn(&Attempt) + Send + Sync>;
+
+/// Decides how often and how patiently requests to a moodle are repeated.
+/// Only transient failures (timeouts, dropped connections, 5xx answers) are retried. Requests that
+/// change something on moodle are only repeated if they never reached it
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    request_timeout: Option<Duration>,
+    total_timeout: Option<Duration>,
+    on_attempt: Option<AttemptHook>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            request_timeout: Some(Duration::from_secs(30)),
+            total_timeout: Some(Duration::from_secs(120)),
+            on_attempt: None,
+        }
+    }
+}
+
+impl Debug for RetryPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("base_delay", &self.base_delay)
+            .field("max_delay", &self.max_delay)
+            .field("request_timeout", &self.request_timeout)
+            .field("total_timeout", &self.total_timeout)
+            .field("on_attempt", &self.on_attempt.is_some())
+            .finish()
+    }
+}
+
+impl RetryPolicy {
+    /// a single attempt without any timeouts, like a plain reqwest call
+    pub fn none() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 1,
+            request_timeout: None,
+            total_timeout: None,
+            ..RetryPolicy::default()
+        }
+    }
+
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// delay before the second attempt, doubled for every further attempt
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// limit for a single attempt, including reading the body
+    pub fn request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// limit for all attempts and the delays in between
+    pub fn total_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.total_timeout = timeout;
+        self
+    }
+
+    /// gets called after every attempt, e.g. for logging
+    pub fn on_attempt(mut self, hook: impl Fn(&Attempt) + Send + Sync + 'static) -> Self {
+        self.on_attempt = Some(Arc::new(hook));
+        self
+    }
+
+    pub(crate) fn get_request_timeout(&self) -> Option<Duration> {
+        self.request_timeout
+    }
+
+    /// exponential backoff with "equal jitter": half of the delay is fixed, the other half random
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_delay);
+        let half = exponential / 2;
+        let jitter = rand::thread_rng().gen_range(0..=half.as_millis() as u64);
+        half + Duration::from_millis(jitter)
+    }
+
+    /// run `send` until it succeeds, fails permanently or the policy gives up.
+    /// Without `idempotent` a request that might have been processed already isnt sent again
+    pub(crate) async fn run<T, F, Fut>(
+        &self,
+        target: &str,
+        idempotent: bool,
+        send: F,
+    ) -> Result<T, MoodleError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, MoodleError>>,
+    {
+        let attempts = async {
+            let mut number = 1;
+            loop {
+                let result = send().await;
+                let retry_in = match &result {
+                    Err(why)
+                        if why.is_transient()
+                            && (idempotent || why.is_unsent())
+                            && number < self.max_attempts =>
+                    {
+                        Some(self.backoff(number))
+                    }
+                    _ => None,
+                };
+                if let Some(hook) = &self.on_attempt {
+                    hook(&Attempt {
+                        target,
+                        number,
+                        error: result.as_ref().err(),
+                        retry_in,
+                    });
+                }
+                match retry_in {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => return result,
+                }
+                number += 1;
+            }
+        };
+
+        match self.total_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, attempts)
+                .await
+                .unwrap_or(Err(MoodleError::Timeout)),
+            None => attempts.await,
+        }
+    }
+}
@@ -0,0 +1,201 @@
+mod common;
+
+use common::{FakeMoodle, COURSE_ID, TOKEN};
+use hyper::StatusCode;
+use moodle::data::course_traversal::get_course_info;
+use moodle::data::gen_module::{EntryValue, GenModule};
+use moodle::data::labels::Locale;
+use moodle::Moodle;
+
+/// serve a course that only contains the less common activities
+fn activity_course(server: &FakeMoodle) {
+    let path = common::fixture_path("course_contents_activities.json");
+    server.respond(
+        "core_course_get_contents",
+        &std::fs::read_to_string(path).unwrap(),
+    );
+}
+
+fn find(modules: &[GenModule], name: &str) -> GenModule {
+    modules
+        .iter()
+        .find(|module| module.name == name)
+        .unwrap_or_else(|| panic!("no module named {name}"))
+        .clone()
+}
+
+#[tokio::test]
+async fn book_chapters() {
+    let server = FakeMoodle::start().await;
+    activity_course(&server);
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let modules = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+    let book = find(&modules, "Skript als Buch");
+
+    assert_eq!(
+        book.entries["chapter:1"],
+        EntryValue::Text("Einleitung".into())
+    );
+    assert_eq!(
+        book.entries["chapter:2"],
+        EntryValue::Text("Steigung".into())
+    );
+    // the text of the chapter pages is compared
+    let EntryValue::Text(text) = &book.entries["chapter_text:1"] else {
+        panic!("the chapter text is text");
+    };
+    assert!(text.contains("**lineare Funktion**"), "{text}");
+    // the chapter pages themselves arent files
+    assert_eq!(book.files.keys().collect::<Vec<_>>(), vec!["graph.png"]);
+    assert!(!book.entries.contains_key("type"));
+}
+
+#[tokio::test]
+async fn chapters_with_the_same_title_are_kept() {
+    let server = FakeMoodle::start().await;
+    let path = common::fixture_path("course_contents_activities.json");
+    let mut contents: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    let book = contents[0]["modules"]
+        .as_array_mut()
+        .unwrap()
+        .iter_mut()
+        .find(|module| module["modname"] == "book")
+        .unwrap();
+    book["contents"][0]["content"] = r#"[
+        {"title": "Aufgaben", "href": "1/index.html", "subitems": []},
+        {"title": "Aufgaben", "href": "2/index.html", "subitems": []}
+    ]"#
+    .into();
+    server.respond("core_course_get_contents", &contents.to_string());
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let modules = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+    let book = find(&modules, "Skript als Buch");
+
+    assert_eq!(book.entries["chapter:1"], book.entries["chapter:2"]);
+    assert_ne!(
+        book.entries["chapter_text:1"],
+        book.entries["chapter_text:2"]
+    );
+}
+
+#[tokio::test]
+async fn a_failing_chapter_only_drops_its_book() {
+    let server = FakeMoodle::start().await;
+    activity_course(&server);
+    server.fail(
+        "/webservice/pluginfile.php/301/mod_book/chapter/2/index.html",
+        1,
+        StatusCode::NOT_FOUND,
+    );
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let scan = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap();
+
+    assert!(!scan
+        .modules
+        .iter()
+        .any(|module| module.name == "Skript als Buch"));
+    assert_eq!(scan.errors.len(), 1);
+    assert_eq!(scan.errors[0].module_type, "book");
+    assert_eq!(scan.errors[0].module_ids, vec![201]);
+}
+
+#[tokio::test]
+async fn lesson_and_workshop_dates() {
+    let server = FakeMoodle::start().await;
+    activity_course(&server);
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let modules = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+    let lesson = find(&modules, "Lektion Steigung");
+    let workshop = find(&modules, "Peer Review");
+
+    assert_eq!(
+        lesson.entries["time_close"],
+        EntryValue::Timestamp(1682000000)
+    );
+    assert_eq!(lesson.entries["retake"], EntryValue::Bool(true));
+    assert_eq!(
+        workshop.entries["phase"],
+        EntryValue::Label("workshop_phase_submission".into())
+    );
+    assert_eq!(
+        workshop.entries["submission_end"],
+        EntryValue::Timestamp(1681600000)
+    );
+    assert_eq!(
+        workshop.entries["assessment_start"],
+        EntryValue::Timestamp(1681700000)
+    );
+    assert!(!workshop.entries.contains_key("instruct_reviewers"));
+}
+
+#[tokio::test]
+async fn wiki_pages_and_database_entries() {
+    let server = FakeMoodle::start().await;
+    activity_course(&server);
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let modules = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+    let wiki = find(&modules, "Klassenwiki");
+    let database = find(&modules, "Aufgabensammlung");
+    let h5p = find(&modules, "Interaktives Video");
+
+    assert_eq!(
+        wiki.entries["page:Start"],
+        EntryValue::Timestamp(1681400000)
+    );
+    assert_eq!(
+        wiki.entries["page:Formeln"],
+        EntryValue::Timestamp(1681500000)
+    );
+    let request = server
+        .requests()
+        .into_iter()
+        .find(|request| request.function() == Some("mod_wiki_get_subwiki_pages"))
+        .unwrap();
+    assert_eq!(request.params["wikiid"], "6");
+    assert_eq!(database.entries["entries"], EntryValue::Integer(7));
+    assert_eq!(
+        database.entries["time_available_to"],
+        EntryValue::Timestamp(1683000000)
+    );
+    assert!(h5p.files.contains_key("video.h5p"));
+}
+
+#[tokio::test]
+async fn activities_without_details_keep_the_module() {
+    let server = FakeMoodle::start().await;
+    activity_course(&server);
+    server.deny("mod_wiki_get_subwiki_pages");
+    server.deny("mod_data_get_entries");
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let modules = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+    let wiki = find(&modules, "Klassenwiki");
+    let database = find(&modules, "Aufgabensammlung");
+
+    assert_eq!(wiki.entries["first_page"], EntryValue::Text("Start".into()));
+    assert!(!wiki.entries.contains_key("page:Start"));
+    assert!(!database.entries.contains_key("entries"));
+}
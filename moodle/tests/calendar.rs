@@ -0,0 +1,114 @@
+mod common;
+
+use common::{FakeMoodle, COURSE_ID, TOKEN};
+use moodle::data::other_content::calendar::CalendarEvent;
+use moodle::Moodle;
+
+#[tokio::test]
+async fn upcoming_action_events() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let events = client
+        .get_action_events_by_timesort(1681776000, Some(1682380800), 20)
+        .await
+        .unwrap();
+
+    let request = &server.requests()[0];
+    assert_eq!(request.params["timesortfrom"], "1681776000");
+    assert_eq!(request.params["timesortto"], "1682380800");
+    assert_eq!(request.params["limitnum"], "20");
+    assert_eq!(
+        events[0],
+        CalendarEvent {
+            id: 31,
+            name: "Hausaufgabe 1 ist fällig".into(),
+            course_id: Some(COURSE_ID),
+            module_name: Some("assign".into()),
+            instance: Some(1),
+            module_id: Some(103),
+            event_type: "due".into(),
+            start: 1681804800,
+            duration: 0,
+            action_url: Some(format!(
+                "{}/mod/assign/view.php?id=103&action=editsubmission",
+                server.base()
+            )),
+            overdue: true,
+            extra: events[0].extra.clone(),
+        }
+    );
+    // fields without a place in CalendarEvent are kept
+    assert_eq!(events[0].extra["activityname"], "Hausaufgabe 1");
+    assert_eq!(events[1].module_name.as_deref(), Some("quiz"));
+    assert_eq!(events[1].module_id, Some(108));
+    assert!(!events[1].overdue);
+}
+
+#[tokio::test]
+async fn action_events_of_a_course() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let events = client
+        .get_action_events_by_course(COURSE_ID, 0, None, 50)
+        .await
+        .unwrap();
+
+    let request = &server.requests()[0];
+    assert_eq!(request.params["courseid"], COURSE_ID.to_string());
+    assert!(!request.params.contains_key("timesortto"));
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].name, "Test Lineare Funktionen endet");
+    assert_eq!(events[0].event_type, "close");
+}
+
+#[tokio::test]
+async fn action_events_prefer_the_course_module_id() {
+    let server = FakeMoodle::start().await;
+    let path = common::fixture_path("webservice/core_calendar_get_action_events_by_course.json");
+    let mut events: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    events["events"][0]["course_module_id"] = 109.into();
+    server.respond(
+        "core_calendar_get_action_events_by_course",
+        &events.to_string(),
+    );
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let events = client
+        .get_action_events_by_course(COURSE_ID, 0, None, 50)
+        .await
+        .unwrap();
+
+    assert_eq!(events[0].module_id, Some(109));
+}
+
+#[tokio::test]
+async fn calendar_events_include_site_events() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let events = client
+        .get_calendar_events(&[COURSE_ID], 1681776000, None)
+        .await
+        .unwrap();
+
+    let request = &server.requests()[0];
+    assert_eq!(
+        request.params["events[courseids][0]"],
+        COURSE_ID.to_string()
+    );
+    assert_eq!(request.params["options[timestart]"], "1681776000");
+    // the due date lies in the past, calendar events dont carry moodle's overdue flag
+    assert_eq!(events[0].course_id, Some(COURSE_ID));
+    assert_eq!(events[0].action_url, None);
+    assert_eq!(events[0].module_id, None);
+    assert!(events[0].overdue);
+    // site events belong to no course or activity
+    assert_eq!(events[1].course_id, None);
+    assert_eq!(events[1].module_name, None);
+    assert_eq!(events[1].instance, None);
+    assert_eq!(events[1].end(), 1682067600 + 7200);
+    assert!(!events[1].overdue);
+}
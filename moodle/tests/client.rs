@@ -0,0 +1,325 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{FakeMoodle, PASSWORD, TOKEN, USERNAME};
+use hyper::Method;
+use moodle::data::other_content::submission_status::SubmissionStatus;
+use moodle::{Moodle, MoodleError, MOBILE_SERVICE};
+
+#[tokio::test]
+async fn login_with_password() {
+    let server = FakeMoodle::start().await;
+
+    let client = Moodle::new_with_login(server.base(), USERNAME, PASSWORD)
+        .await
+        .unwrap();
+
+    let courses = client.get_courses(None).await.unwrap();
+    assert_eq!(courses.courses[0].fullname, "Mathematik 10a");
+}
+
+#[tokio::test]
+async fn login_posts_the_credentials() {
+    let server = FakeMoodle::start().await;
+
+    let login = Moodle::login(server.base(), USERNAME, PASSWORD, "local_mobile")
+        .await
+        .unwrap();
+
+    assert_eq!(login.private_token, Some(format!("private-{TOKEN}")));
+    let request = &server.requests()[0];
+    assert_eq!(request.method, Method::POST);
+    assert_eq!(request.query, None);
+    assert_eq!(request.params["password"], PASSWORD);
+    assert_eq!(request.params["service"], "local_mobile");
+}
+
+#[tokio::test]
+async fn login_uses_the_app_service_by_default() {
+    let server = FakeMoodle::start().await;
+
+    Moodle::new_with_login(server.base(), USERNAME, PASSWORD)
+        .await
+        .unwrap();
+
+    assert_eq!(server.requests()[0].params["service"], MOBILE_SERVICE);
+}
+
+#[tokio::test]
+async fn login_with_wrong_password() {
+    let server = FakeMoodle::start().await;
+
+    let err = Moodle::new_with_login(server.base(), USERNAME, "wrong")
+        .await
+        .unwrap_err();
+
+    match err {
+        MoodleError::Login { errorcode, .. } => assert_eq!(errorcode, "invalidlogin"),
+        err => panic!("expected a login error, got {err:?}"),
+    }
+}
+
+#[tokio::test]
+async fn invalid_token() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), "expired".into());
+
+    let err = client.get_courses(None).await.unwrap_err();
+
+    assert!(matches!(err, MoodleError::InvalidToken), "{err:?}");
+}
+
+#[tokio::test]
+async fn function_not_in_service() {
+    let server = FakeMoodle::start().await;
+    server.deny("mod_quiz_get_quizzes_by_courses");
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let err = client.get_quizzes_for_course(2).await.unwrap_err();
+
+    assert!(matches!(err, MoodleError::AccessDenied { .. }), "{err:?}");
+}
+
+#[tokio::test]
+async fn undecodable_answer_keeps_the_body() {
+    let server = FakeMoodle::start().await;
+    server.respond("mod_url_get_urls_by_courses", r#"{"urls": "nope"}"#);
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let err = client.get_urls_for_course(2).await.unwrap_err();
+
+    match err {
+        MoodleError::Decode { body, .. } => assert_eq!(body, r#"{"urls": "nope"}"#),
+        err => panic!("expected a decode error, got {err:?}"),
+    }
+}
+
+#[tokio::test]
+async fn download_file() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+    let url = format!(
+        "{}/webservice/pluginfile.php/104/mod_resource/content/0/skript.pdf?forcedownload=1",
+        server.base()
+    );
+
+    let file = client.download_file(url, 8000000).await.unwrap();
+
+    let expected = std::fs::read(common::fixture_path("files/skript.pdf")).unwrap();
+    assert_eq!(file, expected);
+}
+
+#[tokio::test]
+async fn download_file_over_the_limit() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+    let url = format!(
+        "{}/webservice/pluginfile.php/104/mod_resource/content/0/skript.pdf",
+        server.base()
+    );
+
+    let file = client.download_file(url, 10).await;
+
+    assert!(matches!(
+        file,
+        Err(MoodleError::TooLarge {
+            size: 24,
+            max_size: 10
+        })
+    ));
+}
+
+#[tokio::test]
+async fn chunked_download_stops_at_the_limit() {
+    let server = FakeMoodle::start().await;
+    server.chunked();
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+    let url = format!(
+        "{}/webservice/pluginfile.php/104/mod_resource/content/0/skript.pdf",
+        server.base()
+    );
+
+    let mut written = Vec::new();
+    let result = client.download_to(&url, 10, &mut written).await;
+
+    // aborted with the chunk that passed the limit, long before the 24 bytes of the file
+    match result {
+        Err(MoodleError::TooLarge { size, max_size }) => {
+            assert_eq!(max_size, 10);
+            assert!(size > 10 && size <= 16);
+        }
+        other => panic!("expected TooLarge, got {other:?}"),
+    }
+    assert!(written.len() <= 10);
+}
+
+#[tokio::test]
+async fn download_reports_file_info() {
+    let server = FakeMoodle::start().await;
+    server.chunked();
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+    let url = format!(
+        "{}/webservice/pluginfile.php/107/mod_folder/content/0/blatt1.pdf?forcedownload=1",
+        server.base()
+    );
+
+    let mut written = Vec::new();
+    let download = client
+        .download_to(&url, 8000000, &mut written)
+        .await
+        .unwrap();
+
+    let expected = std::fs::read(common::fixture_path("files/blatt1.pdf")).unwrap();
+    assert_eq!(written, expected);
+    assert_eq!(download.filename.as_deref(), Some("blatt1.pdf"));
+    assert_eq!(download.mime_type.as_deref(), Some("application/pdf"));
+    assert_eq!(download.size, expected.len() as u64);
+}
+
+#[tokio::test]
+async fn download_to_path() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+    let url = format!(
+        "{}/webservice/pluginfile.php/104/mod_resource/content/0/skript.pdf",
+        server.base()
+    );
+    let dir = std::env::temp_dir().join(format!("moodle-download-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("skript.pdf");
+
+    client.download_to_path(&url, 8000000, &path).await.unwrap();
+    let written = std::fs::read(&path).unwrap();
+    // a failed download doesnt leave a partial file behind
+    let too_large = client.download_to_path(&url, 10, &path).await;
+    let exists = path.exists();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let expected = std::fs::read(common::fixture_path("files/skript.pdf")).unwrap();
+    assert_eq!(written, expected);
+    assert!(matches!(too_large, Err(MoodleError::TooLarge { .. })));
+    assert!(!exists);
+}
+
+#[tokio::test]
+async fn clients_share_their_connections() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+    // a client that was stored on disk and read again
+    let stored = serde_json::to_string(&client).unwrap();
+    let restored: Moodle = serde_json::from_str(&stored).unwrap();
+
+    client.get_courses(None).await.unwrap();
+    restored.get_courses(None).await.unwrap();
+    restored.get_course_contents(2).await.unwrap();
+
+    assert_eq!(server.connections(), 1);
+}
+
+#[tokio::test]
+async fn requests_identify_the_client() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    client.get_courses(None).await.unwrap();
+
+    let headers = &server.requests()[0].headers;
+    assert!(headers["user-agent"]
+        .to_str()
+        .unwrap()
+        .starts_with("moodle/"));
+    assert!(headers["accept-encoding"]
+        .to_str()
+        .unwrap()
+        .contains("gzip"));
+}
+
+#[tokio::test]
+async fn modules_of_several_courses_are_grouped_by_course() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let assignments = client.get_assignments_for_courses(&[2, 3]).await.unwrap();
+
+    assert_eq!(assignments[&2][0].cmid, 103);
+    assert!(assignments[&3].is_empty());
+    assert_eq!(server.calls("mod_assign_get_assignments"), 1);
+}
+
+#[tokio::test]
+async fn submission_status() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let status = client.get_submission_status(1).await.unwrap();
+
+    assert_eq!(server.requests()[0].params["assignid"], "1");
+    assert_eq!(
+        status,
+        SubmissionStatus {
+            status: Some("submitted".into()),
+            last_modified: Some(1681790000),
+            grading_status: Some("graded".into()),
+            grade: Some("80,00 / 100,00".into()),
+            feedback_comments: Some(
+                "<p>Gut gemacht, achte auf die <strong>Einheiten</strong>.</p>".into()
+            ),
+            extension_due_date: Some(1681891200),
+            extra: status.extra.clone(),
+        }
+    );
+}
+
+#[tokio::test]
+async fn submission_status_without_submission() {
+    let server = FakeMoodle::start().await;
+    server.respond(
+        "mod_assign_get_submission_status",
+        r#"{"lastattempt":{"submissionsenabled":true,"locked":false,"graded":false,"canedit":true,"caneditowner":true,"cansubmit":false,"extensionduedate":null,"blindmarking":false,"gradingstatus":"notgraded","usergroups":[]},"warnings":[]}"#,
+    );
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let status = client.get_submission_status(1).await.unwrap();
+
+    assert_eq!(status.status, None);
+    assert_eq!(status.grading_status.as_deref(), Some("notgraded"));
+    assert_eq!(status.grade, None);
+    assert_eq!(status.extension_due_date, None);
+}
+
+#[tokio::test]
+async fn site_info_is_cached() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let site_info = client.site_info().await.unwrap();
+    assert_eq!(site_info.fullname, "Erika Musterfrau");
+    assert_eq!(site_info.userid, 5);
+    assert!(site_info.allows("mod_assign_get_assignments"));
+    assert!(!site_info.allows("mod_scorm_get_scorms_by_courses"));
+
+    client.clone().site_info().await.unwrap();
+    assert_eq!(server.calls("core_webservice_get_site_info"), 1);
+}
+
+#[tokio::test]
+async fn concurrency_is_limited_per_client() {
+    let server = FakeMoodle::start().await;
+    server.delay(
+        "core_course_get_enrolled_courses_by_timeline_classification",
+        Duration::from_millis(50),
+    );
+    let client = Moodle::new_with_token(server.base(), TOKEN.into()).with_concurrency(2);
+
+    // clones share the limit
+    let requests = (0..6).map(|_| {
+        let client = client.clone();
+        async move { client.get_courses(None).await }
+    });
+    for courses in futures::future::join_all(requests).await {
+        courses.unwrap();
+    }
+
+    assert_eq!(server.max_in_flight(), 2);
+}
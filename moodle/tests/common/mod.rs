@@ -0,0 +1,302 @@
+//! An in-process fake moodle that serves the recorded answers in `tests/fixtures`.
+//!
+//! `webservice/<wsfunction>.json` is returned for web service calls, `files/<name>` (or the whole
+//! path below pluginfile.php) for pluginfile downloads.
+//! The placeholder `{{base}}` inside a fixture is replaced with the url of the server.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use hyper::header::HeaderMap;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+
+pub const TOKEN: &str = "0123456789abcdef0123456789abcdef";
+pub const USERNAME: &str = "student";
+pub const PASSWORD: &str = "hunter2&#+";
+pub const COURSE_ID: i64 = 2;
+
+/// A request the fake moodle received
+#[derive(Debug, Clone)]
+pub struct Recorded {
+    pub method: Method,
+    pub path: String,
+    pub query: Option<String>,
+    pub params: HashMap<String, String>,
+    pub headers: HeaderMap,
+}
+
+impl Recorded {
+    pub fn function(&self) -> Option<&str> {
+        self.params.get("wsfunction").map(String::as_str)
+    }
+}
+
+#[derive(Default)]
+struct State {
+    base: String,
+    overrides: HashMap<String, String>,
+    failures: HashMap<String, (usize, StatusCode)>,
+    delays: HashMap<String, Duration>,
+    chunked: bool,
+    requests: Vec<Recorded>,
+    connections: usize,
+    in_flight: usize,
+    max_in_flight: usize,
+}
+
+#[derive(Clone)]
+pub struct FakeMoodle {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+}
+
+impl FakeMoodle {
+    pub async fn start() -> FakeMoodle {
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let service_state = state.clone();
+        let make_service = make_service_fn(move |_| {
+            let state = service_state.clone();
+            state.lock().unwrap().connections += 1;
+            async move { Ok::<_, Infallible>(service_fn(move |req| handle(state.clone(), req))) }
+        });
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+        let addr = server.local_addr();
+        state.lock().unwrap().base = format!("http://{addr}");
+        tokio::spawn(server);
+
+        FakeMoodle { addr, state }
+    }
+
+    pub fn base(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// answer `function` with `body` instead of the recorded fixture
+    pub fn respond(&self, function: &str, body: &str) -> &Self {
+        let mut state = self.state.lock().unwrap();
+        state.overrides.insert(function.into(), body.into());
+        self
+    }
+
+    /// answer `function` like a moodle that doesnt allow it for the token's service
+    pub fn deny(&self, function: &str) -> &Self {
+        self.respond(
+            function,
+            r#"{"exception":"webservice_access_exception","errorcode":"accessexception","message":"Access control exception"}"#,
+        )
+    }
+
+    /// answer the next `times` calls of `target` (a wsfunction or a path) with `status`
+    pub fn fail(&self, target: &str, times: usize, status: StatusCode) -> &Self {
+        let mut state = self.state.lock().unwrap();
+        state.failures.insert(target.into(), (times, status));
+        self
+    }
+
+    /// wait before answering calls of `target` (a wsfunction or a path)
+    pub fn delay(&self, target: &str, delay: Duration) -> &Self {
+        let mut state = self.state.lock().unwrap();
+        state.delays.insert(target.into(), delay);
+        self
+    }
+
+    /// send files in small chunks without a content-length, like moodle does for large files
+    pub fn chunked(&self) -> &Self {
+        self.state.lock().unwrap().chunked = true;
+        self
+    }
+
+    /// number of tcp connections that were opened to the server
+    pub fn connections(&self) -> usize {
+        self.state.lock().unwrap().connections
+    }
+
+    /// the highest number of requests that were handled at the same time
+    pub fn max_in_flight(&self) -> usize {
+        self.state.lock().unwrap().max_in_flight
+    }
+
+    pub fn requests(&self) -> Vec<Recorded> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    pub fn calls(&self, function: &str) -> usize {
+        self.requests()
+            .iter()
+            .filter(|request| request.function() == Some(function))
+            .count()
+    }
+}
+
+pub fn fixture_path(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(path)
+}
+
+async fn handle(
+    state: Arc<Mutex<State>>,
+    req: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    {
+        let mut state = state.lock().unwrap();
+        state.in_flight += 1;
+        state.max_in_flight = state.max_in_flight.max(state.in_flight);
+    }
+    let response = answer(&state, req).await;
+    state.lock().unwrap().in_flight -= 1;
+    Ok(response)
+}
+
+async fn answer(state: &Mutex<State>, req: Request<Body>) -> Response<Body> {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let query = req.uri().query().map(String::from);
+    let headers = req.headers().clone();
+    let body = hyper::body::to_bytes(req.into_body())
+        .await
+        .unwrap_or_default();
+
+    let mut params: HashMap<String, String> = HashMap::new();
+    if let Some(query) = &query {
+        params.extend(url::form_urlencoded::parse(query.as_bytes()).into_owned());
+    }
+    params.extend(url::form_urlencoded::parse(&body).into_owned());
+
+    let recorded = Recorded {
+        method,
+        path: path.clone(),
+        query,
+        params: params.clone(),
+        headers,
+    };
+    let target = recorded.function().unwrap_or(&path).to_string();
+    let (delay, failure) = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(recorded);
+        let delay = state.delays.get(&target).copied();
+        let failure = match state.failures.get_mut(&target) {
+            Some((times, status)) if *times > 0 => {
+                *times -= 1;
+                Some(*status)
+            }
+            _ => None,
+        };
+        (delay, failure)
+    };
+    if let Some(delay) = delay {
+        tokio::time::sleep(delay).await;
+    }
+    if let Some(status) = failure {
+        return Response::builder()
+            .status(status)
+            .body(Body::empty())
+            .unwrap();
+    }
+
+    match path.as_str() {
+        "/webservice/rest/server.php" => web_service(state, &params),
+        "/login/token.php" => login(&params),
+        _ if path.starts_with("/webservice/pluginfile.php/") => {
+            let chunked = state.lock().unwrap().chunked;
+            plugin_file(&path, &params, chunked)
+        }
+        _ => not_found(),
+    }
+}
+
+fn web_service(state: &Mutex<State>, params: &HashMap<String, String>) -> Response<Body> {
+    if params.get("wstoken").map(String::as_str) != Some(TOKEN) {
+        return json(
+            r#"{"exception":"moodle_exception","errorcode":"invalidtoken","message":"Invalid token - token not found"}"#
+                .into(),
+        );
+    }
+    let function = params.get("wsfunction").cloned().unwrap_or_default();
+
+    let state = state.lock().unwrap();
+    let body = match state.overrides.get(&function) {
+        Some(body) => body.clone(),
+        None => match std::fs::read_to_string(fixture_path(&format!("webservice/{function}.json"))) {
+            Ok(body) => body,
+            // moodle answers like this for functions that arent part of the service
+            Err(_) => r#"{"exception":"webservice_access_exception","errorcode":"accessexception","message":"Access control exception"}"#.into(),
+        },
+    };
+    json(body.replace("{{base}}", &state.base))
+}
+
+fn login(params: &HashMap<String, String>) -> Response<Body> {
+    let valid = params.get("username").map(String::as_str) == Some(USERNAME)
+        && params.get("password").map(String::as_str) == Some(PASSWORD);
+    if valid {
+        json(format!(
+            r#"{{"token":"{TOKEN}","privatetoken":"private-{TOKEN}"}}"#
+        ))
+    } else {
+        json(
+            r#"{"error":"Invalid login, please try again","errorcode":"invalidlogin","stacktrace":null,"debuginfo":null,"reproductionlink":null}"#
+                .into(),
+        )
+    }
+}
+
+fn plugin_file(path: &str, params: &HashMap<String, String>, chunked: bool) -> Response<Body> {
+    if params.get("token").map(String::as_str) != Some(TOKEN) {
+        return Response::builder()
+            .status(StatusCode::FORBIDDEN)
+            .body(Body::empty())
+            .unwrap();
+    }
+    // files with a common name like the index.html of book chapters are stored with their path
+    let name = path.rsplit('/').next().unwrap_or_default();
+    let full_path = path.trim_start_matches("/webservice/pluginfile.php/");
+    let bytes = match std::fs::read(fixture_path(&format!("files/{full_path}")))
+        .or_else(|_| std::fs::read(fixture_path(&format!("files/{name}"))))
+    {
+        Ok(bytes) => bytes,
+        Err(_) => return not_found(),
+    };
+    let body = if chunked {
+        let (mut sender, body) = Body::channel();
+        tokio::spawn(async move {
+            for chunk in bytes.chunks(8) {
+                if sender.send_data(chunk.to_vec().into()).await.is_err() {
+                    break;
+                }
+            }
+        });
+        body
+    } else {
+        Body::from(bytes)
+    };
+    Response::builder()
+        .header("content-type", "application/pdf")
+        .header(
+            "content-disposition",
+            format!("attachment; filename=\"{name}\""),
+        )
+        .body(body)
+        .unwrap()
+}
+
+fn json(body: String) -> Response<Body> {
+    Response::builder()
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+fn not_found() -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .body(Body::empty())
+        .unwrap()
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use common::{FakeMoodle, COURSE_ID, TOKEN};
+use moodle::data::other_content::completion::{
+    ActivityCompletion, CompletionState, CompletionTracking,
+};
+use moodle::{Moodle, MoodleError};
+
+#[tokio::test]
+async fn completion_of_a_course() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let statuses = client.get_activities_completion(COURSE_ID).await.unwrap();
+
+    let request = server
+        .requests()
+        .into_iter()
+        .find(|request| {
+            request.function() == Some("core_completion_get_activities_completion_status")
+        })
+        .unwrap();
+    assert_eq!(request.params["courseid"], COURSE_ID.to_string());
+    assert_eq!(request.params["userid"], "5");
+    assert_eq!(statuses.len(), 4);
+    assert_eq!(
+        statuses[0],
+        ActivityCompletion {
+            module_id: 102,
+            module_name: "label".into(),
+            instance: 1,
+            state: CompletionState::Complete,
+            tracking: CompletionTracking::Manual,
+            time_completed: Some(1681400000),
+            overridden_by: None,
+            extra: statuses[0].extra.clone(),
+        }
+    );
+    assert_eq!(statuses[0].extra["uservisible"], true);
+    assert!(!statuses[1].is_complete());
+    assert!(!statuses[1].is_manual());
+    assert_eq!(statuses[1].time_completed, None);
+    assert!(statuses[2].is_manual());
+    assert_eq!(statuses[3].state, CompletionState::CompletePass);
+    assert!(statuses[3].is_complete());
+}
+
+#[tokio::test]
+async fn mark_activity_done() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    client.update_completion_manually(104, true).await.unwrap();
+
+    let request = &server.requests()[0];
+    assert_eq!(request.params["cmid"], "104");
+    assert_eq!(request.params["completed"], "1");
+}
+
+#[tokio::test]
+async fn refused_completion_update() {
+    let server = FakeMoodle::start().await;
+    server.respond(
+        "core_completion_update_activity_completion_status_manually",
+        r#"{"status":false,"warnings":[]}"#,
+    );
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let result = client.update_completion_manually(104, false).await;
+
+    assert!(matches!(result, Err(MoodleError::Exception { .. })));
+    assert_eq!(server.requests()[0].params["completed"], "0");
+}
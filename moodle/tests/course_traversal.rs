@@ -0,0 +1,409 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{FakeMoodle, COURSE_ID, TOKEN};
+use hyper::StatusCode;
+use moodle::data::course_contents::{Id, ModuleKey};
+use moodle::data::course_traversal::{get_course_info, get_course_info_many};
+use moodle::data::gen_module::{EntryValue, Fallback, GenModule};
+use moodle::data::icons;
+use moodle::data::labels::Locale;
+use moodle::retry::RetryPolicy;
+use moodle::{Moodle, MoodleError};
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+/// a fixture answer of the fake server as json
+fn fixture(function: &str) -> Value {
+    let path = common::fixture_path(&format!("webservice/{function}.json"));
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn find(modules: &[GenModule], name: &str) -> GenModule {
+    modules
+        .iter()
+        .find(|module| module.name == name)
+        .unwrap_or_else(|| panic!("no module named {name}"))
+        .clone()
+}
+
+#[tokio::test]
+async fn generates_every_module_and_section() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let modules = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+
+    // 9 course modules + 2 sections
+    assert_eq!(modules.len(), 11);
+    let mut ids: Vec<_> = modules.iter().map(|module| module.get_id()).collect();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 11);
+}
+
+#[tokio::test]
+async fn assignment_entries() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let modules = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+    let assignment = find(&modules, "Hausaufgabe 1");
+
+    assert_eq!(assignment.get_id(), ModuleKey::Module(103));
+    assert_eq!(
+        assignment.entries["due_date"],
+        EntryValue::Timestamp(1681804800)
+    );
+    assert_eq!(
+        assignment.entries["grading_due_date"],
+        EntryValue::Timestamp(1682409600)
+    );
+    // a cutoff date of 0 means there is none
+    assert!(!assignment.entries.contains_key("cutoff_date"));
+    assert!(assignment.files.contains_key("aufgaben.pdf"));
+    // state of the token's own submission
+    assert_eq!(
+        assignment.entries["submission_status"],
+        EntryValue::Label("submission_submitted".into())
+    );
+    assert_eq!(
+        assignment.entries["extension_due_date"],
+        EntryValue::Timestamp(1681891200)
+    );
+    let EntryValue::Text(rating) = &assignment.entries["rating"] else {
+        panic!("the rating is text");
+    };
+    assert!(rating.starts_with("80,00 / 100,00\n"), "{rating}");
+    assert!(rating.contains("Gut gemacht"), "{rating}");
+}
+
+#[tokio::test]
+async fn assignments_without_submission_status() {
+    let server = FakeMoodle::start().await;
+    server.deny("mod_assign_get_submission_status");
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let modules = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+    let assignment = find(&modules, "Hausaufgabe 1");
+
+    assert_eq!(
+        assignment.entries["due_date"],
+        EntryValue::Timestamp(1681804800)
+    );
+    assert!(!assignment.entries.contains_key("submission_status"));
+    assert!(!assignment.entries.contains_key("rating"));
+}
+
+#[tokio::test]
+async fn a_failing_submission_status_only_drops_its_assignment() {
+    let server = FakeMoodle::start().await;
+    let mut contents = fixture("core_course_get_contents");
+    let mut second = contents[1]["modules"][0].clone();
+    second["id"] = 110.into();
+    second["name"] = "Hausaufgabe 2".into();
+    contents[1]["modules"].as_array_mut().unwrap().push(second);
+    server.respond("core_course_get_contents", &contents.to_string());
+    let mut assignments = fixture("mod_assign_get_assignments");
+    let mut second = assignments["courses"][0]["assignments"][0].clone();
+    second["id"] = 2.into();
+    second["cmid"] = 110.into();
+    second["name"] = "Hausaufgabe 2".into();
+    let list = assignments["courses"][0]["assignments"]
+        .as_array_mut()
+        .unwrap();
+    list.push(second);
+    server.respond("mod_assign_get_assignments", &assignments.to_string());
+    // only the first status request fails, a 404 isnt retried
+    server.fail("mod_assign_get_submission_status", 1, StatusCode::NOT_FOUND);
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let scan = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap();
+
+    assert_eq!(scan.errors.len(), 1);
+    assert_eq!(scan.errors[0].module_type, "assign");
+    assert!(matches!(*scan.errors[0].error, MoodleError::Status(_)));
+    let [failed] = scan.errors[0].module_ids[..] else {
+        panic!("only one assignment failed");
+    };
+    let assignments: Vec<_> = scan
+        .modules
+        .iter()
+        .filter(|module| module.name.starts_with("Hausaufgabe"))
+        .collect();
+    assert_eq!(assignments.len(), 1);
+    assert_ne!(assignments[0].get_id(), ModuleKey::Module(failed));
+    assert!(assignments[0].entries.contains_key("submission_status"));
+    // the other module types are still there
+    assert_eq!(scan.modules.len(), 11);
+}
+
+#[tokio::test]
+async fn resource_urls_drop_the_revision() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let modules = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+    let resource = find(&modules, "Skript");
+
+    let url = &resource.files["skript.pdf"];
+    assert!(url.contains("/mod_resource/content/0/skript.pdf"), "{url}");
+}
+
+#[tokio::test]
+async fn unknown_modules_use_the_course_module() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let modules = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+    let publication = find(&modules, "Abgabeordner");
+
+    assert_eq!(
+        publication.entries["type"],
+        EntryValue::Text("publication".into())
+    );
+    assert_eq!(publication.fallback, Some(Fallback::UnknownType));
+}
+
+#[tokio::test]
+async fn modules_missing_from_their_type_keep_the_course_contents() {
+    let server = FakeMoodle::start().await;
+    // e.g. an assignment that is restricted for this user
+    server.respond(
+        "mod_assign_get_assignments",
+        r#"{"courses":[{"id":2,"fullname":"Mathematik 10a","assignments":[]}],"warnings":[]}"#,
+    );
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let modules = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+    let assignment = find(&modules, "Hausaufgabe 1");
+
+    assert_eq!(modules.len(), 11);
+    assert_eq!(assignment.fallback, Some(Fallback::NotReturned));
+    assert_eq!(
+        assignment.entries["type"],
+        EntryValue::Text("assign".into())
+    );
+    assert_eq!(
+        assignment.entries["Fällig:"],
+        EntryValue::Timestamp(1681804800)
+    );
+    assert_eq!(find(&modules, "Skript").fallback, None);
+}
+
+#[tokio::test]
+async fn sections_are_generated() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let modules = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+    let section = find(&modules, "Woche 1");
+
+    assert!(matches!(section.get_id(), ModuleKey::Section(_)));
+    assert_eq!(section.mod_icon_url, icons::SECTION_ICON_URL);
+    assert!(icons::bundled(&section.mod_icon_url).is_some());
+    assert!(matches!(
+        &section.entries["summary"],
+        EntryValue::Text(summary) if summary.contains("Lineare Funktionen")
+    ));
+}
+
+#[tokio::test]
+async fn order_is_deterministic() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let first = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+    let second = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+
+    assert_eq!(first, second);
+    // module types are sorted by name and followed by the sections
+    let names: Vec<_> = first.iter().map(|module| module.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "Hausaufgabe 1",
+            "Arbeitsblätter",
+            "Ankündigungen",
+            "Willkommen",
+            "Formelsammlung",
+            "Abgabeordner",
+            "Test Lineare Funktionen",
+            "Skript",
+            "GeoGebra",
+            "Allgemeines",
+            "Woche 1",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn module_types_are_requested_concurrently() {
+    let server = FakeMoodle::start().await;
+    for function in [
+        "mod_assign_get_assignments",
+        "mod_folder_get_folders_by_courses",
+        "mod_forum_get_forums_by_courses",
+        "mod_label_get_labels_by_courses",
+        "mod_page_get_pages_by_courses",
+        "mod_quiz_get_quizzes_by_courses",
+        "mod_resource_get_resources_by_courses",
+        "mod_url_get_urls_by_courses",
+    ] {
+        server.delay(function, Duration::from_millis(100));
+    }
+    let client = Moodle::new_with_token(server.base(), TOKEN.into()).with_concurrency(2);
+
+    get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap();
+
+    assert_eq!(server.max_in_flight(), 2);
+}
+
+#[tokio::test]
+async fn many_courses_share_one_request_per_module_type() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let courses = get_course_info_many(&client, &[COURSE_ID, 3], Locale::De)
+        .await
+        .unwrap();
+
+    assert_eq!(server.calls("core_course_get_contents"), 2);
+    assert_eq!(server.calls("mod_assign_get_assignments"), 1);
+    let request = server
+        .requests()
+        .into_iter()
+        .find(|request| request.function() == Some("mod_assign_get_assignments"))
+        .unwrap();
+    assert_eq!(request.params["courseids[0]"], "2");
+    assert_eq!(request.params["courseids[1]"], "3");
+
+    let single = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+    assert_eq!(courses[&COURSE_ID].modules, single);
+    assert!(courses.contains_key(&3));
+}
+
+#[tokio::test]
+async fn module_types_outside_the_service_use_the_course_module() {
+    let server = FakeMoodle::start().await;
+    let path = common::fixture_path("webservice/core_webservice_get_site_info.json");
+    let mut site_info: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    site_info["functions"]
+        .as_array_mut()
+        .unwrap()
+        .retain(|function| function["name"] != "mod_quiz_get_quizzes_by_courses");
+    server.respond("core_webservice_get_site_info", &site_info.to_string());
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let modules = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+
+    let quiz = find(&modules, "Test Lineare Funktionen");
+    assert_eq!(quiz.entries["type"], EntryValue::Text("quiz".into()));
+    assert_eq!(quiz.fallback, Some(Fallback::NotInService));
+    assert_eq!(server.calls("mod_quiz_get_quizzes_by_courses"), 0);
+}
+
+#[tokio::test]
+async fn services_without_site_info_query_every_module_type() {
+    let server = FakeMoodle::start().await;
+    server.deny("core_webservice_get_site_info");
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let modules = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+
+    assert_eq!(modules.len(), 11);
+    assert_eq!(server.calls("mod_quiz_get_quizzes_by_courses"), 1);
+}
+
+#[tokio::test]
+async fn a_failing_site_info_doesnt_stop_the_scan() {
+    let server = FakeMoodle::start().await;
+    server.fail(
+        "core_webservice_get_site_info",
+        1,
+        StatusCode::INTERNAL_SERVER_ERROR,
+    );
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let client = Moodle::new_with_token(server.base(), TOKEN.into())
+        .with_retry_policy(RetryPolicy::none())
+        .with_warnings(sender);
+
+    let scan = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap();
+
+    // every module type is queried, like without site info
+    assert_eq!(scan.modules.len(), 11);
+    assert!(scan.errors.is_empty());
+    assert_eq!(server.calls("mod_quiz_get_quizzes_by_courses"), 1);
+    let warning = receiver.try_recv().unwrap();
+    assert_eq!(warning.function, "core_webservice_get_site_info");
+}
+
+#[tokio::test]
+async fn a_failing_module_type_only_drops_its_modules() {
+    let server = FakeMoodle::start().await;
+    server.deny("mod_quiz_get_quizzes_by_courses");
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let scan = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap();
+
+    assert_eq!(scan.modules.len(), 10);
+    assert!(!scan
+        .modules
+        .iter()
+        .any(|module| module.name == "Test Lineare Funktionen"));
+    assert_eq!(scan.errors.len(), 1);
+    assert_eq!(scan.errors[0].module_type, "quiz");
+    assert_eq!(scan.errors[0].module_ids, vec![108]);
+    assert!(matches!(
+        *scan.errors[0].error,
+        MoodleError::AccessDenied { .. }
+    ));
+    assert!(scan.is_missing(ModuleKey::Module(108)));
+    assert!(!scan.is_missing(ModuleKey::Module(101)));
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use common::{FakeMoodle, TOKEN};
+use moodle::data::other_content::discussion::ForumPost;
+use moodle::data::other_content::file::FileInfo;
+use moodle::Moodle;
+
+#[tokio::test]
+async fn discussions_of_a_forum() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let discussions = client.get_forum_discussions(1, 0, 10).await.unwrap();
+
+    let request = &server.requests()[0];
+    assert_eq!(request.params["forumid"], "1");
+    assert_eq!(request.params["perpage"], "10");
+    let discussion = &discussions[0];
+    assert_eq!(discussion.id, 12);
+    assert_eq!(discussion.forum_id, 1);
+    assert_eq!(discussion.first_post_id, 51);
+    assert_eq!(discussion.subject, "Test am Freitag");
+    assert_eq!(discussion.author, "Max Mustermann");
+    assert_eq!(discussion.num_replies, 1);
+    assert_eq!(discussion.modified, 1681720000);
+    assert_eq!(discussion.attachments[0].filename, "blatt2.pdf");
+    assert_eq!(
+        discussion.link,
+        format!("{}/mod/forum/discuss.php?d=12", server.base())
+    );
+}
+
+#[tokio::test]
+async fn posts_of_a_discussion() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let posts = client.get_discussion_posts(12).await.unwrap();
+
+    let request = &server.requests()[0];
+    assert_eq!(request.params["discussionid"], "12");
+    assert_eq!(posts.len(), 2);
+    assert_eq!(posts[0].parent_id, None);
+    assert_eq!(
+        posts[0].attachments,
+        vec![FileInfo {
+            filename: "blatt2.pdf".into(),
+            fileurl: format!(
+                "{}/webservice/pluginfile.php/201/mod_forum/attachment/51/blatt2.pdf",
+                server.base()
+            ),
+        }]
+    );
+    assert_eq!(
+        posts[1],
+        ForumPost {
+            id: 52,
+            discussion_id: 12,
+            parent_id: Some(51),
+            subject: "Re: Test am Freitag".into(),
+            author: "Erika Musterfrau".into(),
+            author_picture_url: Some(format!(
+                "{}/pluginfile.php/31/user/icon/boost/f1?rev=1",
+                server.base()
+            )),
+            message: "<p>Dürfen wir die Formelsammlung benutzen?</p>".into(),
+            attachments: vec![],
+            created: 1681720000,
+            link: Some(format!("{}/mod/forum/discuss.php?d=12#p52", server.base())),
+            extra: posts[1].extra.clone(),
+        }
+    );
+}
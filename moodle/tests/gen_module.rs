@@ -0,0 +1,186 @@
+use moodle::data::comparable::{compare, diff_module_entries};
+use moodle::data::course_contents::{Id, ModuleKey};
+use moodle::data::gen_module::{EntryValue, GenModule, GenModuleBuilder, TextMode};
+use moodle::data::icons;
+
+#[test]
+fn legacy_snapshots_are_converted() {
+    let snapshot = r#"{
+        "entries": {
+            "Öffnungsdatum": "<t:1681200000:F>",
+            "Zeitspanne in Sekunden": "1800",
+            "Versuche": "3",
+            "Größe": "1.50MB",
+            "Wiederholbar": "Ja",
+            "Link": "https://www.geogebra.org/classic",
+            "Bescheibung": "Lineare Funktionen"
+        },
+        "files": {},
+        "mod_icon_url": "",
+        "name": "Test Lineare Funktionen",
+        "link": null,
+        "id": 108
+    }"#;
+
+    let module: GenModule = serde_json::from_str(snapshot).unwrap();
+
+    assert_eq!(module.get_id(), ModuleKey::Module(108));
+    assert_eq!(
+        module.entries["time_open"],
+        EntryValue::Timestamp(1681200000)
+    );
+    assert_eq!(module.entries["time_limit"], EntryValue::Duration(1800));
+    assert!(!module.entries.contains_key("Zeitspanne in Sekunden"));
+    assert_eq!(module.entries["attempts"], EntryValue::Integer(3));
+    assert_eq!(module.entries["Größe"], EntryValue::Bytes(3 << 19));
+    assert_eq!(module.entries["retake"], EntryValue::Bool(true));
+    assert_eq!(
+        module.entries["link"],
+        EntryValue::Url("https://www.geogebra.org/classic".into())
+    );
+    assert_eq!(
+        module.entries["description"],
+        EntryValue::Text("Lineare Funktionen".into())
+    );
+}
+
+#[test]
+fn legacy_time_limit_of_zero_is_dropped() {
+    let snapshot = r#"{"entries":{"Zeitspanne in Sekunden":"0"},"files":{},"mod_icon_url":"","name":"Quiz","link":null,"id":1}"#;
+
+    let module: GenModule = serde_json::from_str(snapshot).unwrap();
+
+    assert!(module.entries.is_empty());
+}
+
+#[test]
+fn legacy_chat_times_become_dates() {
+    let snapshot = r#"{"entries":{"Startzeit":"1681300000"},"files":{},"mod_icon_url":"","name":"Chat","link":null,"id":1}"#;
+    let typed = r#"{"entries":{"chat_time":{"type":"integer","value":1681300000}},"files":{},"mod_icon_url":"","name":"Chat","link":null,"id":1}"#;
+    let unset = r#"{"entries":{"Startzeit":"0"},"files":{},"mod_icon_url":"","name":"Chat","link":null,"id":1}"#;
+
+    for snapshot in [snapshot, typed] {
+        let module: GenModule = serde_json::from_str(snapshot).unwrap();
+        assert_eq!(
+            module.entries["chat_time"],
+            EntryValue::Timestamp(1681300000)
+        );
+    }
+    let module: GenModule = serde_json::from_str(unset).unwrap();
+    assert!(module.entries.is_empty());
+}
+
+#[test]
+fn typed_entries_survive_a_round_trip() {
+    let snapshot = r#"{
+        "entries": {
+            "Öffnungsdatum": {"type": "timestamp", "value": 1681200000},
+            "Zeitlimit": {"type": "duration", "value": 1800},
+            "Versuche": {"type": "text", "value": "3"}
+        },
+        "files": {},
+        "mod_icon_url": "",
+        "name": "Test Lineare Funktionen",
+        "link": null,
+        "id": 108
+    }"#;
+
+    let module: GenModule = serde_json::from_str(snapshot).unwrap();
+    let reloaded: GenModule =
+        serde_json::from_str(&serde_json::to_string(&module).unwrap()).unwrap();
+
+    // typed text stays text even if it looks like a number
+    assert_eq!(module.entries["attempts"], EntryValue::Text("3".into()));
+    assert_eq!(module.entries["time_limit"], EntryValue::Duration(1800));
+    assert_eq!(module, reloaded);
+}
+
+#[test]
+fn legacy_sections_get_a_section_key() {
+    let snapshot = r#"[
+        {"entries":{},"files":{},"mod_icon_url":"https://cdn.discordapp.com/attachments/1092233307867070554/1095647451739865108/section.png","name":"Woche 1","link":null,"id":110000},
+        {"entries":{},"files":{},"mod_icon_url":"","name":"Aufgabe","link":null,"id":20000}
+    ]"#;
+
+    let modules: Vec<GenModule> = serde_json::from_str(snapshot).unwrap();
+
+    assert_eq!(modules[0].get_id(), ModuleKey::Section(11));
+    assert_eq!(modules[0].mod_icon_url, icons::SECTION_ICON_URL);
+    // module ids that happen to be a multiple of 10000 stay modules
+    assert_eq!(modules[1].get_id(), ModuleKey::Module(20000));
+}
+
+#[test]
+fn sections_and_modules_with_the_same_id_are_different() {
+    let section = GenModuleBuilder::new(
+        ModuleKey::Section(5),
+        icons::SECTION_ICON_URL.into(),
+        "Woche 1".into(),
+        None,
+    )
+    .build();
+    let module =
+        GenModuleBuilder::new(ModuleKey::Module(5), "".into(), "Quiz".into(), None).build();
+    let stored = serde_json::to_string(&[section.clone(), module.clone()]).unwrap();
+
+    let reloaded: Vec<GenModule> = serde_json::from_str(&stored).unwrap();
+    let comparison = compare(vec![section, module], reloaded);
+
+    assert!(stored.contains(r#""id":{"kind":"section","id":5}"#));
+    assert_eq!(comparison.common.len(), 2);
+    assert!(comparison
+        .common
+        .iter()
+        .all(|(old, new)| old.get_id() == new.get_id()));
+}
+
+#[test]
+fn stripped_descriptions_take_the_markdown() {
+    let snapshot = r#"{
+        "entries": {
+            "intro": {"type": "text", "value": " Bringt einen  Taschenrechner  mit! "},
+            "content": {"type": "text", "value": " Seite 1 "}
+        },
+        "files": {},
+        "mod_icon_url": "",
+        "name": "Test Lineare Funktionen",
+        "link": null,
+        "id": {"kind": "module", "id": 108}
+    }"#;
+    let mut old: GenModule = serde_json::from_str(snapshot).unwrap();
+    let mut builder = GenModuleBuilder::new(ModuleKey::Module(108), "".into(), "Test".into(), None);
+    builder
+        .string(
+            "intro",
+            "<p>Bringt einen <b>Taschenrechner</b> mit!</p>".into(),
+        )
+        .string("content", "<p>Seite 2</p>".into());
+    let new = builder.build();
+
+    old.migrate_texts(&new);
+    let changes = diff_module_entries(old.entries, new.entries);
+
+    // only the text that really changed is left
+    assert_eq!(changes.len(), 1);
+    assert!(changes.contains_key("content"));
+}
+
+#[test]
+fn strings_can_be_plain_text() {
+    let mut builder = GenModuleBuilder::new(ModuleKey::Module(1), "".into(), "Test".into(), None);
+    builder
+        .string("markdown", "<p>Die <b>Abgabe</b></p>".into())
+        .text_mode(TextMode::Plain)
+        .string("plain", "<p>Die <b>Abgabe</b></p>".into());
+
+    let module = builder.build();
+
+    assert_eq!(
+        module.entries["markdown"],
+        EntryValue::Text("Die **Abgabe**".into())
+    );
+    assert_eq!(
+        module.entries["plain"],
+        EntryValue::Text("Die Abgabe".into())
+    );
+}
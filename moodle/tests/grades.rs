@@ -0,0 +1,78 @@
+mod common;
+
+use common::{FakeMoodle, COURSE_ID, TOKEN};
+use moodle::data::other_content::grades::{CourseGrade, GradeItem};
+use moodle::Moodle;
+
+#[tokio::test]
+async fn course_grades() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let grades = client.get_course_grades().await.unwrap();
+
+    assert_eq!(
+        grades,
+        vec![
+            CourseGrade {
+                course_id: COURSE_ID,
+                grade: Some("85,00".into()),
+                raw_grade: Some(85.0),
+                extra: grades[0].extra.clone(),
+            },
+            // not graded yet
+            CourseGrade {
+                course_id: 3,
+                grade: None,
+                raw_grade: None,
+                extra: grades[1].extra.clone(),
+            },
+        ]
+    );
+    assert!(grades[0].extra.contains_key("rank"));
+}
+
+#[tokio::test]
+async fn grade_items_of_the_tokens_user() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let items = client.get_grade_items(COURSE_ID).await.unwrap();
+
+    let request = server
+        .requests()
+        .into_iter()
+        .find(|request| request.function() == Some("gradereport_user_get_grade_items"))
+        .unwrap();
+    assert_eq!(request.params["courseid"], COURSE_ID.to_string());
+    // the user id comes from the site info
+    assert_eq!(request.params["userid"], "5");
+
+    assert_eq!(
+        items[0],
+        GradeItem {
+            id: 21,
+            name: "Hausaufgabe 1".into(),
+            item_type: "mod".into(),
+            module_name: Some("assign".into()),
+            module_id: Some(103),
+            grade: Some(80.0),
+            grade_formatted: Some("80,00".into()),
+            min: 0.0,
+            max: 100.0,
+            percentage: Some(80.0),
+            feedback: Some("<p>Gut gemacht, achte auf die <strong>Einheiten</strong>.</p>".into()),
+            graded: Some(1682000000),
+            extra: items[0].extra.clone(),
+        }
+    );
+    // not graded yet
+    assert_eq!(items[1].grade, None);
+    assert_eq!(items[1].grade_formatted, None);
+    assert_eq!(items[1].percentage, None);
+    assert_eq!(items[1].feedback, None);
+    // the course total
+    assert_eq!(items[2].name, "course");
+    assert_eq!(items[2].module_id, None);
+    assert_eq!(items[2].percentage, Some(85.0));
+}
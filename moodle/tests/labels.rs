@@ -0,0 +1,68 @@
+use moodle::data::gen_module::{EntryValue, GenModule};
+use moodle::data::labels::Locale;
+
+#[test]
+fn every_locale_has_the_same_keys() {
+    let keys =
+        |locale: Locale| -> Vec<&str> { locale.catalog().iter().map(|(key, _)| *key).collect() };
+
+    assert_eq!(keys(Locale::En), keys(Locale::De));
+}
+
+#[test]
+fn keys_are_translated() {
+    assert_eq!(Locale::En.label("due_date"), "Due date");
+    assert_eq!(Locale::De.label("due_date"), "Abgabedatum");
+    assert_eq!(
+        Locale::En.label("chapter:Einleitung"),
+        "Chapter: Einleitung"
+    );
+    assert_eq!(
+        Locale::De.label("chapter:Einleitung"),
+        "Kapitel: Einleitung"
+    );
+    // date labels from moodle and keys of custom handlers are shown as they are
+    assert_eq!(Locale::En.label("Fällig:"), "Fällig:");
+    assert_eq!(Locale::En.label("Abgabetermin"), "Abgabetermin");
+}
+
+#[test]
+fn legacy_german_snapshots_use_machine_keys() {
+    let snapshot = r#"{
+        "entries": {
+            "Phase": "Einreichung",
+            "Abgabestatus": "Abgegeben",
+            "Kapitel: Einleitung": "Lineare Funktionen",
+            "Anzahl an Disskusionen": "4",
+            "Fällig:": "<t:1681200000:F>"
+        },
+        "files": {},
+        "mod_icon_url": "",
+        "name": "Projekt",
+        "link": null,
+        "id": 110
+    }"#;
+
+    let module: GenModule = serde_json::from_str(snapshot).unwrap();
+    let keys: Vec<&str> = module.entries.keys().map(String::as_str).collect();
+
+    assert_eq!(
+        keys,
+        [
+            "phase",
+            "submission_status",
+            "chapter:Einleitung",
+            "discussions",
+            "Fällig:"
+        ]
+    );
+    assert_eq!(
+        module.entries["phase"],
+        EntryValue::Label("workshop_phase_submission".into())
+    );
+    assert_eq!(
+        module.entries["submission_status"],
+        EntryValue::Label("submission_submitted".into())
+    );
+    assert_eq!(module.entries["discussions"], EntryValue::Integer(4));
+}
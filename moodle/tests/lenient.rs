@@ -0,0 +1,274 @@
+mod common;
+
+use common::{FakeMoodle, COURSE_ID, TOKEN};
+use moodle::data::course_traversal::get_course_info;
+use moodle::data::gen_module::EntryValue;
+use moodle::data::labels::Locale;
+use moodle::data::lenient::ParseWarning;
+use moodle::Moodle;
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn missing_fields_default() {
+    let server = FakeMoodle::start().await;
+    // an older moodle without revision, timemodified and contentfiles
+    server.respond(
+        "mod_page_get_pages_by_courses",
+        r#"{"pages":[{"id":1,"coursemodule":106,"course":2,"name":"Formelsammlung","intro":"","content":"<p>m = 2</p>"}],"warnings":[]}"#,
+    );
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let modules = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+
+    let page = modules
+        .iter()
+        .find(|module| module.name == "Formelsammlung")
+        .unwrap();
+    assert_eq!(page.entries["revision"], EntryValue::Integer(0));
+    assert!(!page.entries.contains_key("time_modified"));
+}
+
+#[tokio::test]
+async fn unknown_fields_are_kept() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let pages = client.get_pages_for_course(COURSE_ID).await.unwrap();
+
+    assert_eq!(pages[0].extra["display"], 5);
+    assert_eq!(pages[0].extra["legacyfileslast"], serde_json::Value::Null);
+    // known fields dont end up in the side map
+    assert!(!pages[0].extra.contains_key("coursemodule"));
+}
+
+#[tokio::test]
+async fn unknown_fields_of_other_answers_are_kept() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let site_info = client.site_info().await.unwrap();
+    let sections = client.get_course_contents(COURSE_ID).await.unwrap();
+
+    assert_eq!(site_info.extra["firstname"], "Erika");
+    assert_eq!(sections[0].section_info.extra["uservisible"], true);
+    // the modules of a section arent unknown
+    assert!(!sections[0].section_info.extra.contains_key("modules"));
+}
+
+#[tokio::test]
+async fn broken_elements_are_skipped_and_reported() {
+    let server = FakeMoodle::start().await;
+    server.respond(
+        "mod_url_get_urls_by_courses",
+        r#"{"urls":[
+            {"id":1,"coursemodule":105,"course":2,"name":"GeoGebra","externalurl":"https://www.geogebra.org/classic"},
+            {"id":2,"coursemodule":"kaputt","course":2,"name":"Kaputt"}
+        ],"warnings":[]}"#,
+    );
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let client = Moodle::new_with_token(server.base(), TOKEN.into()).with_warnings(sender);
+
+    let urls = client.get_urls_for_course(COURSE_ID).await.unwrap();
+
+    assert_eq!(urls.len(), 1);
+    assert_eq!(urls[0].name, "GeoGebra");
+    let ParseWarning {
+        function,
+        item,
+        index,
+        message,
+    } = receiver.try_recv().unwrap();
+    assert_eq!(function, "mod_url_get_urls_by_courses");
+    assert!(item.ends_with("Url"), "{item}");
+    assert_eq!(index, 1);
+    assert!(message.contains("invalid type"), "{message}");
+    assert!(receiver.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn a_broken_course_module_doesnt_stop_the_scan() {
+    let server = FakeMoodle::start().await;
+    let path = common::fixture_path("webservice/core_course_get_contents.json");
+    let mut contents: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    contents[1]["modules"][0]["id"] = "kaputt".into();
+    server.respond("core_course_get_contents", &contents.to_string());
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let client = Moodle::new_with_token(server.base(), TOKEN.into()).with_warnings(sender);
+
+    let modules = get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+
+    // the assignment is gone, the other 8 modules and both sections are still there
+    assert_eq!(modules.len(), 10);
+    assert!(!modules.iter().any(|module| module.name == "Hausaufgabe 1"));
+    let warning = receiver.try_recv().unwrap();
+    assert_eq!(warning.function, "core_course_get_contents");
+    assert_eq!(warning.index, 0);
+}
+
+/// the fixture answer of `function`, every list at `pointers` gets a copy of its first element
+/// in which `field` has the wrong type
+fn with_broken_elements(function: &str, pointers: &[(&str, &str)]) -> String {
+    let path = common::fixture_path(&format!("webservice/{function}.json"));
+    let mut answer: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    for (pointer, field) in pointers {
+        let list = answer.pointer_mut(pointer).unwrap().as_array_mut().unwrap();
+        let mut broken = list[0].clone();
+        broken[*field] = match broken[*field] {
+            Value::String(_) => 42.into(),
+            _ => "kaputt".into(),
+        };
+        list.push(broken);
+    }
+    answer.to_string()
+}
+
+/// functions of the warnings that were reported so far
+fn reported(receiver: &mut mpsc::UnboundedReceiver<ParseWarning>) -> Vec<String> {
+    let mut functions = vec![];
+    while let Ok(warning) = receiver.try_recv() {
+        functions.push(warning.function);
+    }
+    functions
+}
+
+#[tokio::test]
+async fn grades_skip_broken_elements() {
+    let server = FakeMoodle::start().await;
+    for (function, pointers) in [
+        (
+            "gradereport_overview_get_course_grades",
+            vec![("/grades", "courseid")],
+        ),
+        (
+            "gradereport_user_get_grade_items",
+            vec![
+                ("/usergrades/0/gradeitems", "id"),
+                ("/usergrades", "gradeitems"),
+            ],
+        ),
+    ] {
+        server.respond(function, &with_broken_elements(function, &pointers));
+    }
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let client = Moodle::new_with_token(server.base(), TOKEN.into()).with_warnings(sender);
+
+    assert_eq!(client.get_course_grades().await.unwrap().len(), 2);
+    assert_eq!(client.get_grade_items(COURSE_ID).await.unwrap().len(), 3);
+    assert_eq!(reported(&mut receiver).len(), 3);
+}
+
+#[tokio::test]
+async fn calendar_and_completion_skip_broken_elements() {
+    let server = FakeMoodle::start().await;
+    for (function, field) in [
+        ("core_calendar_get_action_events_by_timesort", "/events"),
+        ("core_calendar_get_action_events_by_course", "/events"),
+        ("core_calendar_get_calendar_events", "/events"),
+    ] {
+        server.respond(function, &with_broken_elements(function, &[(field, "id")]));
+    }
+    let function = "core_completion_get_activities_completion_status";
+    server.respond(
+        function,
+        &with_broken_elements(function, &[("/statuses", "cmid")]),
+    );
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let client = Moodle::new_with_token(server.base(), TOKEN.into()).with_warnings(sender);
+
+    let by_time = client.get_action_events_by_timesort(0, None, 20).await;
+    let by_course = client
+        .get_action_events_by_course(COURSE_ID, 0, None, 20)
+        .await;
+    let events = client.get_calendar_events(&[COURSE_ID], 0, None).await;
+    let completion = client.get_activities_completion(COURSE_ID).await;
+
+    assert_eq!(by_time.unwrap().len(), 2);
+    assert_eq!(by_course.unwrap().len(), 1);
+    assert_eq!(events.unwrap().len(), 2);
+    assert_eq!(completion.unwrap().len(), 4);
+    assert_eq!(reported(&mut receiver).len(), 4);
+}
+
+#[tokio::test]
+async fn forums_skip_broken_elements() {
+    let server = FakeMoodle::start().await;
+    let function = "mod_forum_get_forum_discussions";
+    let pointers = [
+        ("/discussions", "id"),
+        ("/discussions/0/attachments", "filename"),
+    ];
+    server.respond(function, &with_broken_elements(function, &pointers));
+    let function = "mod_forum_get_discussion_posts";
+    let pointers = [("/posts", "id"), ("/posts/0/attachments", "filename")];
+    server.respond(function, &with_broken_elements(function, &pointers));
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let client = Moodle::new_with_token(server.base(), TOKEN.into()).with_warnings(sender);
+
+    let discussions = client.get_forum_discussions(1, 0, 10).await.unwrap();
+    let posts = client.get_discussion_posts(1).await.unwrap();
+
+    assert_eq!(discussions.len(), 1);
+    assert_eq!(discussions[0].attachments.len(), 1);
+    assert_eq!(posts.len(), 2);
+    assert_eq!(posts[0].attachments.len(), 1);
+    assert_eq!(reported(&mut receiver).len(), 4);
+}
+
+#[tokio::test]
+async fn messages_skip_broken_elements() {
+    let server = FakeMoodle::start().await;
+    let function = "message_popup_get_popup_notifications";
+    server.respond(
+        function,
+        &with_broken_elements(function, &[("/notifications", "id")]),
+    );
+    let function = "core_message_get_conversations";
+    let pointers = [
+        ("/conversations", "id"),
+        ("/conversations/0/members", "id"),
+        ("/conversations/0/messages", "id"),
+    ];
+    server.respond(function, &with_broken_elements(function, &pointers));
+    let function = "core_message_get_conversation_messages";
+    let pointers = [("/members", "id"), ("/messages", "id")];
+    server.respond(function, &with_broken_elements(function, &pointers));
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let client = Moodle::new_with_token(server.base(), TOKEN.into()).with_warnings(sender);
+
+    let notifications = client.get_popup_notifications(10).await.unwrap();
+    let conversations = client.get_conversations(10).await.unwrap();
+    let messages = client.get_conversation_messages(1, 10).await.unwrap();
+
+    assert_eq!(notifications.len(), 2);
+    assert_eq!(conversations.len(), 1);
+    assert_eq!(conversations[0].members.len(), 1);
+    assert_eq!(messages.len(), 2);
+    let warnings = reported(&mut receiver);
+    assert_eq!(warnings.len(), 6, "{warnings:?}");
+}
+
+#[tokio::test]
+async fn submission_status_skips_broken_elements() {
+    let server = FakeMoodle::start().await;
+    let function = "mod_assign_get_submission_status";
+    let pointers = [
+        ("/feedback/plugins", "type"),
+        ("/feedback/plugins/0/editorfields", "text"),
+    ];
+    server.respond(function, &with_broken_elements(function, &pointers));
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let client = Moodle::new_with_token(server.base(), TOKEN.into()).with_warnings(sender);
+
+    let status = client.get_submission_status(1).await.unwrap();
+
+    assert!(status.feedback_comments.is_some());
+    assert_eq!(reported(&mut receiver).len(), 2);
+}
@@ -0,0 +1,101 @@
+use moodle::data::markdown::{html_to_markdown, html_to_text};
+
+#[test]
+fn paragraphs_and_formatting() {
+    let html = "<p>Liebe Klasse,</p>\n<p>morgen schreiben wir den <strong>Test</strong> über <em>lineare Funktionen</em>.<br>Bringt einen Taschenrechner mit!</p>";
+
+    assert_eq!(
+        html_to_markdown(html),
+        "Liebe Klasse,\n\nmorgen schreiben wir den **Test** über *lineare Funktionen*.\nBringt einen Taschenrechner mit!"
+    );
+}
+
+#[test]
+fn spaces_stay_outside_of_markers() {
+    assert_eq!(
+        html_to_markdown("achte auf die<strong> Einheiten </strong>."),
+        "achte auf die **Einheiten** ."
+    );
+    assert_eq!(html_to_markdown("<b> </b>leer"), "leer");
+}
+
+#[test]
+fn links() {
+    assert_eq!(
+        html_to_markdown(
+            r#"Siehe <a href="https://www.geogebra.org/classic?lang=de&amp;x=1">GeoGebra</a>"#
+        ),
+        "Siehe [GeoGebra](https://www.geogebra.org/classic?lang=de&x=1)"
+    );
+    assert_eq!(
+        html_to_markdown(r#"<a href="https://example.org/a_b">https://example.org/a_b</a>"#),
+        "https://example.org/a_b"
+    );
+    // plain urls arent escaped, other text is
+    assert_eq!(
+        html_to_markdown("https://example.org/a_b und a_b"),
+        "https://example.org/a_b und a\\_b"
+    );
+}
+
+#[test]
+fn lists() {
+    let html = "<p>Aufgaben:</p><ol><li>Nr. 3</li><li>Nr. 5<ul><li>a</li><li>b</li></ul></li></ol><p>Viel Erfolg</p>";
+
+    assert_eq!(
+        html_to_markdown(html),
+        "Aufgaben:\n\n1. Nr. 3\n2. Nr. 5\n  - a\n  - b\n\nViel Erfolg"
+    );
+}
+
+#[test]
+fn headings_quotes_and_code() {
+    let html =
+        "<h3>Hinweis</h3><blockquote><p>Zitat</p></blockquote><pre>fn main() {\n    42\n}</pre>";
+
+    assert_eq!(
+        html_to_markdown(html),
+        "**Hinweis**\n\n> Zitat\n\n```\nfn main() {\n    42\n}\n```"
+    );
+}
+
+#[test]
+fn entities_scripts_and_broken_html() {
+    assert_eq!(
+        html_to_markdown("Gr&ouml;&szlig;e &lt; 5&nbsp;cm &#8211; &#x263A; &unknown;"),
+        "Größe < 5 cm – ☺ &unknown;"
+    );
+    assert_eq!(
+        html_to_markdown("<script>alert(1)</script><style>p {}</style><!-- kommentar -->Text"),
+        "Text"
+    );
+    assert_eq!(
+        html_to_markdown("<b>nicht <i>geschlossen</b> a < b"),
+        "**nicht geschlossen** a < b"
+    );
+}
+
+#[test]
+fn named_entities() {
+    assert_eq!(
+        html_to_markdown("caf&eacute; &amp; cr&egrave;me &frac12; &ndash; &quot;&alpha; &le; &Omega;&quot; &sigmaf; &yuml;"),
+        "café & crème ½ – \"α ≤ Ω\" ς ÿ"
+    );
+}
+
+#[test]
+fn tables() {
+    let html = "<table><tr><th>Aufgabe</th><th>Punkte</th></tr><tr><td>1</td><td>5</td></tr></table><p>Summe</p>";
+
+    assert_eq!(html_to_markdown(html), "Aufgabe | Punkte\n1 | 5\n\nSumme");
+}
+
+#[test]
+fn plain_text() {
+    let html = r#"<h4>Hinweis</h4><p>Die <strong>Abgabe</strong> ist <a href="https://example.org/a_b">hier</a> m&ouml;glich.</p><ul><li>a_b</li></ul>"#;
+
+    assert_eq!(
+        html_to_text(html),
+        "Hinweis\n\nDie Abgabe ist hier (https://example.org/a_b) möglich.\n\n- a_b"
+    );
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use common::{FakeMoodle, TOKEN};
+use moodle::data::other_content::messages::{ConversationMember, ConversationMessage};
+use moodle::{Moodle, MoodleError};
+
+#[tokio::test]
+async fn popup_notifications() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let notifications = client.get_popup_notifications(20).await.unwrap();
+
+    let request = server
+        .requests()
+        .into_iter()
+        .find(|request| request.function() == Some("message_popup_get_popup_notifications"))
+        .unwrap();
+    assert_eq!(request.params["useridto"], "5");
+    assert_eq!(request.params["limit"], "20");
+    assert_eq!(notifications[0].id, 82);
+    assert_eq!(notifications[0].subject, "Feedback zu Hausaufgabe 1");
+    assert!(notifications[0]
+        .message
+        .contains("<strong>Feedback</strong>"));
+    assert_eq!(
+        notifications[0].url,
+        Some(format!("{}/mod/assign/view.php?id=103", server.base()))
+    );
+    assert!(!notifications[0].read);
+    // an empty html message falls back to the short one
+    assert_eq!(notifications[1].message, "Willkommen bei Moodle");
+    assert_eq!(notifications[1].url, None);
+}
+
+#[tokio::test]
+async fn conversations_and_messages() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let conversations = client.get_conversations(20).await.unwrap();
+    let messages = client.get_conversation_messages(7, 20).await.unwrap();
+
+    let conversation = &conversations[0];
+    // private conversations are named after the other member
+    assert_eq!(conversation.name, "Max Mustermann");
+    assert_eq!(
+        conversation.members,
+        vec![ConversationMember {
+            id: 3,
+            fullname: "Max Mustermann".into()
+        }]
+    );
+    assert_eq!(conversation.unread_count, 1);
+    assert_eq!(conversation.last_message_id, Some(33));
+
+    let request = server
+        .requests()
+        .into_iter()
+        .find(|request| request.function() == Some("core_message_get_conversation_messages"))
+        .unwrap();
+    assert_eq!(request.params["convid"], "7");
+    assert_eq!(request.params["currentuserid"], "5");
+    assert_eq!(
+        messages[0],
+        ConversationMessage {
+            id: 33,
+            conversation_id: 7,
+            author_id: 3,
+            author: Some("Max Mustermann".into()),
+            text: "<p>Bitte gib die Korrektur bis Montag ab.</p>".into(),
+            created: 1682010000,
+            extra: Default::default(),
+        }
+    );
+    assert_eq!(messages[1].author.as_deref(), Some("Erika Musterfrau"));
+}
+
+#[tokio::test]
+async fn send_instant_message() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let id = client.send_instant_message(3, "Ok, danke!").await.unwrap();
+
+    let request = &server.requests()[0];
+    assert_eq!(request.params["messages[0][touserid]"], "3");
+    assert_eq!(request.params["messages[0][text]"], "Ok, danke!");
+    assert_eq!(id, 34);
+}
+
+#[tokio::test]
+async fn refused_instant_message() {
+    let server = FakeMoodle::start().await;
+    server.respond(
+        "core_message_send_instant_messages",
+        r#"[{"msgid":-1,"clientmsgid":null,"errormessage":"Diese Person hat Sie blockiert."}]"#,
+    );
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+
+    let result = client.send_instant_message(3, "Hallo").await;
+
+    match result {
+        Err(MoodleError::Exception { message, .. }) => {
+            assert_eq!(message, "Diese Person hat Sie blockiert.")
+        }
+        other => panic!("expected an exception, got {other:?}"),
+    }
+}
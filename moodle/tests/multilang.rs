@@ -0,0 +1,100 @@
+mod common;
+
+use common::{FakeMoodle, COURSE_ID, TOKEN};
+use moodle::data::course_traversal::get_course_info;
+use moodle::data::gen_module::EntryValue;
+use moodle::data::labels::Locale;
+use moodle::data::multilang::resolve;
+use moodle::Moodle;
+
+const NAME: &str = r#"<span lang="de" class="multilang">Hausaufgabe</span> <span lang="en" class="multilang">Homework</span>"#;
+
+#[test]
+fn the_requested_language_is_kept() {
+    assert_eq!(resolve(NAME, "en"), "Homework");
+    assert_eq!(resolve(NAME, "de"), "Hausaufgabe");
+    // the parent language and then the first block are the fallbacks
+    assert_eq!(resolve(NAME, "en_us"), "Homework");
+    assert_eq!(resolve(NAME, "fr"), "Hausaufgabe");
+}
+
+#[test]
+fn groups_are_resolved_on_their_own() {
+    let text = format!(
+        r#"<p>{NAME}: <span class="multilang" lang="en">read</span><span class="multilang" lang="de">lesen</span></p>"#
+    );
+
+    assert_eq!(resolve(&text, "de"), "<p>Hausaufgabe: lesen</p>");
+    assert_eq!(
+        resolve(
+            r#"<lang lang="de">Kapitel</lang><lang lang="en">Chapter</lang> 1"#,
+            "en"
+        ),
+        "Chapter 1"
+    );
+}
+
+#[test]
+fn other_spans_are_left_alone() {
+    let single = r#"<span lang="en" class="multilang">only english</span>"#;
+    let plain = r#"<span lang="en">quote</span><span lang="de">Zitat</span>"#;
+
+    assert_eq!(resolve(single, "de"), single);
+    assert_eq!(resolve(plain, "de"), plain);
+}
+
+#[tokio::test]
+async fn the_language_is_sent_with_every_request() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into()).with_language("en");
+
+    client.get_course_contents(COURSE_ID).await.unwrap();
+    Moodle::new_with_token(server.base(), TOKEN.into())
+        .get_course_contents(COURSE_ID)
+        .await
+        .unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests[0].params["moodlewssettinglang"], "en");
+    assert!(!requests[1].params.contains_key("moodlewssettinglang"));
+    // the language alone doesnt switch on moodle's filters
+    assert!(!requests[0].params.contains_key("moodlewssettingfilter"));
+}
+
+#[tokio::test]
+async fn filtering_is_a_separate_option() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into()).with_filter(true);
+
+    client.get_course_contents(COURSE_ID).await.unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests[0].params["moodlewssettingfilter"], "true");
+    assert!(!requests[0].params.contains_key("moodlewssettinglang"));
+}
+
+#[tokio::test]
+async fn unfiltered_answers_are_resolved_locally() {
+    let server = FakeMoodle::start().await;
+    let path = common::fixture_path("webservice/mod_quiz_get_quizzes_by_courses.json");
+    let quizzes = std::fs::read_to_string(path).unwrap().replace(
+        "<p>Zeit: 30 Minuten</p>",
+        r#"<p><span lang=\"de\" class=\"multilang\">Zeit: 30 Minuten<\/span><span lang=\"en\" class=\"multilang\">Time: 30 minutes<\/span></p>"#,
+    );
+    server.respond("mod_quiz_get_quizzes_by_courses", &quizzes);
+    let client = Moodle::new_with_token(server.base(), TOKEN.into()).with_language("en");
+
+    let modules = get_course_info(&client, COURSE_ID, Locale::En)
+        .await
+        .unwrap()
+        .modules;
+    let quiz = modules
+        .iter()
+        .find(|module| module.name == "Test Lineare Funktionen")
+        .unwrap();
+
+    assert_eq!(
+        quiz.entries["description"],
+        EntryValue::Text("Time: 30 minutes".into())
+    );
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use common::{FakeMoodle, COURSE_ID, TOKEN};
+use moodle::data::course_contents::{CourseModule, Generate, Id, ModuleKey};
+use moodle::data::gen_module::{EntryValue, Fallback, GenModule, GenModuleBuilder};
+use moodle::data::labels::Locale;
+use moodle::data::registry::ModuleRegistry;
+use moodle::{group_by_course, Moodle, MoodleError};
+use serde::Deserialize;
+
+/// a plugin the crate doesnt know about
+#[derive(Deserialize)]
+struct Publication {
+    coursemodule: i64,
+    course: i64,
+    duedate: i64,
+}
+
+#[derive(Deserialize)]
+struct Publications {
+    publications: Vec<Publication>,
+}
+
+impl Id for Publication {
+    fn get_id(&self) -> ModuleKey {
+        ModuleKey::Module(self.coursemodule)
+    }
+}
+
+#[async_trait]
+impl Generate for Publication {
+    const FUNCTION: &'static str = "mod_publication_get_publications_by_courses";
+
+    async fn request_many(
+        client: &Moodle,
+        course_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Self>>, MoodleError> {
+        let res: Publications = client.module_request(Self::FUNCTION, course_ids).await?;
+        Ok(group_by_course(course_ids, res.publications, |module| {
+            module.course
+        }))
+    }
+
+    fn gen(self, builder: &mut GenModuleBuilder, _: CourseModule) -> &mut GenModuleBuilder {
+        builder.date("Abgabetermin", self.duedate)
+    }
+}
+
+fn find(modules: &[GenModule], name: &str) -> GenModule {
+    modules
+        .iter()
+        .find(|module| module.name == name)
+        .unwrap_or_else(|| panic!("no module named {name}"))
+        .clone()
+}
+
+#[tokio::test]
+async fn custom_handlers_can_be_registered() {
+    let server = FakeMoodle::start().await;
+    // the fake site info doesnt list the plugin function
+    server.deny("core_webservice_get_site_info");
+    server.respond(
+        "mod_publication_get_publications_by_courses",
+        r#"{"publications":[{"coursemodule":109,"course":2,"duedate":1682500000}],"warnings":[]}"#,
+    );
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+    let registry = ModuleRegistry::default().register::<Publication>("publication");
+
+    let modules = registry
+        .get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+    let publication = find(&modules, "Abgabeordner");
+
+    assert_eq!(
+        publication.entries["Abgabetermin"],
+        EntryValue::Timestamp(1682500000)
+    );
+    assert_eq!(publication.fallback, None);
+    assert_eq!(find(&modules, "Skript").fallback, None);
+}
+
+#[tokio::test]
+async fn an_empty_registry_only_uses_the_course_contents() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), TOKEN.into());
+    let registry = ModuleRegistry::new();
+
+    let modules = registry
+        .get_course_info(&client, COURSE_ID, Locale::De)
+        .await
+        .unwrap()
+        .modules;
+
+    assert_eq!(modules.len(), 11);
+    let quiz = find(&modules, "Test Lineare Funktionen");
+    assert_eq!(quiz.fallback, Some(Fallback::UnknownType));
+    assert!(server
+        .requests()
+        .iter()
+        .all(|request| !request.function().unwrap_or_default().starts_with("mod_")));
+}
+
+#[test]
+fn default_registry_knows_the_builtin_types() {
+    let registry = ModuleRegistry::default();
+
+    assert!(registry.contains("quiz"));
+    assert!(registry.contains("questionnaire"));
+    assert!(!registry.contains("publication"));
+    assert_eq!(registry.modnames().count(), 20);
+}
@@ -0,0 +1,212 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use common::{FakeMoodle, COURSE_ID, TOKEN};
+use hyper::StatusCode;
+use moodle::retry::RetryPolicy;
+use moodle::{Moodle, MoodleError};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const COURSES: &str = "core_course_get_enrolled_courses_by_timeline_classification";
+
+fn fast_policy() -> RetryPolicy {
+    RetryPolicy::default()
+        .base_delay(Duration::from_millis(1))
+        .max_delay(Duration::from_millis(5))
+}
+
+#[tokio::test]
+async fn retries_server_errors() {
+    let server = FakeMoodle::start().await;
+    server.fail(COURSES, 2, StatusCode::SERVICE_UNAVAILABLE);
+    let attempts = Arc::new(Mutex::new(vec![]));
+    let hook_attempts = attempts.clone();
+    let policy = fast_policy().max_attempts(3).on_attempt(move |attempt| {
+        let failed = attempt.error.is_some();
+        hook_attempts.lock().unwrap().push((attempt.number, failed));
+    });
+    let client = Moodle::new_with_token(server.base(), TOKEN.into()).with_retry_policy(policy);
+
+    let courses = client.get_courses(None).await.unwrap();
+
+    assert_eq!(courses.courses.len(), 1);
+    assert_eq!(server.calls(COURSES), 3);
+    assert_eq!(
+        *attempts.lock().unwrap(),
+        vec![(1, true), (2, true), (3, false)]
+    );
+}
+
+#[tokio::test]
+async fn gives_up_after_max_attempts() {
+    let server = FakeMoodle::start().await;
+    server.fail(COURSES, 5, StatusCode::BAD_GATEWAY);
+    let client = Moodle::new_with_token(server.base(), TOKEN.into())
+        .with_retry_policy(fast_policy().max_attempts(2));
+
+    let err = client.get_courses(None).await.unwrap_err();
+
+    assert!(
+        matches!(err, MoodleError::Status(StatusCode::BAD_GATEWAY)),
+        "{err:?}"
+    );
+    assert_eq!(server.calls(COURSES), 2);
+}
+
+#[tokio::test]
+async fn auth_errors_are_not_retried() {
+    let server = FakeMoodle::start().await;
+    let client = Moodle::new_with_token(server.base(), "expired".into())
+        .with_retry_policy(fast_policy().max_attempts(5));
+
+    let err = client.get_courses(None).await.unwrap_err();
+
+    assert!(matches!(err, MoodleError::InvalidToken), "{err:?}");
+    assert_eq!(server.calls(COURSES), 1);
+}
+
+#[tokio::test]
+async fn client_errors_are_not_retried() {
+    let server = FakeMoodle::start().await;
+    server.fail(COURSES, 5, StatusCode::NOT_FOUND);
+    let client = Moodle::new_with_token(server.base(), TOKEN.into())
+        .with_retry_policy(fast_policy().max_attempts(5));
+
+    let err = client.get_courses(None).await.unwrap_err();
+
+    assert!(
+        matches!(err, MoodleError::Status(StatusCode::NOT_FOUND)),
+        "{err:?}"
+    );
+    assert_eq!(server.calls(COURSES), 1);
+}
+
+#[tokio::test]
+async fn slow_answers_time_out_and_are_retried() {
+    let server = FakeMoodle::start().await;
+    server.delay("core_course_get_contents", Duration::from_millis(500));
+    let policy = fast_policy()
+        .max_attempts(2)
+        .request_timeout(Some(Duration::from_millis(50)));
+    let client = Moodle::new_with_token(server.base(), TOKEN.into()).with_retry_policy(policy);
+
+    let err = client.get_course_contents(COURSE_ID).await.unwrap_err();
+
+    assert!(err.is_transient(), "{err:?}");
+    assert_eq!(server.calls("core_course_get_contents"), 2);
+}
+
+#[tokio::test]
+async fn total_timeout_covers_all_attempts() {
+    let server = FakeMoodle::start().await;
+    server.delay("core_course_get_contents", Duration::from_millis(500));
+    let policy = fast_policy()
+        .max_attempts(10)
+        .request_timeout(None)
+        .total_timeout(Some(Duration::from_millis(100)));
+    let client = Moodle::new_with_token(server.base(), TOKEN.into()).with_retry_policy(policy);
+
+    let err = client.get_course_contents(COURSE_ID).await.unwrap_err();
+
+    assert!(matches!(err, MoodleError::Timeout), "{err:?}");
+}
+
+#[tokio::test]
+async fn downloads_are_retried() {
+    let server = FakeMoodle::start().await;
+    let path = "/webservice/pluginfile.php/104/mod_resource/content/0/skript.pdf";
+    server.fail(path, 1, StatusCode::INTERNAL_SERVER_ERROR);
+    let client =
+        Moodle::new_with_token(server.base(), TOKEN.into()).with_retry_policy(fast_policy());
+
+    let file = client
+        .download_file(format!("{}{path}", server.base()), 8000000)
+        .await
+        .unwrap();
+
+    assert!(!file.is_empty());
+}
+
+#[tokio::test]
+async fn timed_out_changes_are_not_sent_again() {
+    let server = FakeMoodle::start().await;
+    server.delay(
+        "core_message_send_instant_messages",
+        Duration::from_millis(500),
+    );
+    let policy = fast_policy()
+        .max_attempts(3)
+        .request_timeout(Some(Duration::from_millis(50)));
+    let client = Moodle::new_with_token(server.base(), TOKEN.into()).with_retry_policy(policy);
+
+    // moodle might still deliver the message, sending it again could deliver it twice
+    let err = client.send_instant_message(3, "Hallo").await.unwrap_err();
+
+    assert!(err.is_transient(), "{err:?}");
+    assert_eq!(server.calls("core_message_send_instant_messages"), 1);
+}
+
+#[tokio::test]
+async fn failed_changes_are_not_sent_again() {
+    let server = FakeMoodle::start().await;
+    let function = "core_completion_update_activity_completion_status_manually";
+    server.fail(function, 1, StatusCode::BAD_GATEWAY);
+    let client = Moodle::new_with_token(server.base(), TOKEN.into())
+        .with_retry_policy(fast_policy().max_attempts(3));
+
+    let err = client
+        .update_completion_manually(104, true)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, MoodleError::Status(_)), "{err:?}");
+    assert_eq!(server.calls(function), 1);
+}
+
+#[tokio::test]
+async fn changes_that_never_reached_moodle_are_retried() {
+    let attempts = Arc::new(Mutex::new(0));
+    let hook_attempts = attempts.clone();
+    let policy = fast_policy().max_attempts(3).on_attempt(move |_| {
+        *hook_attempts.lock().unwrap() += 1;
+    });
+    // nothing listens on this port, so the connection is refused before anything is sent
+    let client =
+        Moodle::new_with_token("http://127.0.0.1:1".into(), TOKEN.into()).with_retry_policy(policy);
+
+    let err = client.send_instant_message(3, "Hallo").await.unwrap_err();
+
+    assert!(err.is_unsent(), "{err:?}");
+    assert_eq!(*attempts.lock().unwrap(), 3);
+}
+
+#[tokio::test]
+async fn connections_that_break_while_reading_are_retried() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let path = common::fixture_path(&format!("webservice/{COURSES}.json"));
+    let body = std::fs::read_to_string(path).unwrap();
+    tokio::spawn(async move {
+        for attempt in 0.. {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0; 4096];
+            let _ = socket.read(&mut request).await.unwrap();
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            socket.write_all(head.as_bytes()).await.unwrap();
+            // the first answer is cut off in the middle of the body
+            let sent = if attempt == 0 { &body[..10] } else { &body[..] };
+            socket.write_all(sent.as_bytes()).await.unwrap();
+        }
+    });
+    let client = Moodle::new_with_token(base, TOKEN.into()).with_retry_policy(fast_policy());
+
+    let courses = client.get_courses(None).await.unwrap();
+
+    assert_eq!(courses.courses.len(), 1);
+}